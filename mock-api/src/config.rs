@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{database::DatasetSize, delay::Delays, faults::Faults, rate_limit::RateLimit};
+
+/// Behavior of the mock server, read from the environment on startup
+/// and changed at runtime with `/admin`
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub delays: Delays,
+    pub rate_limit: RateLimit,
+    pub faults: Faults,
+    pub size: DatasetSize,
+}
+
+impl Config {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let config = Self {
+            delays: Delays::from_env()?,
+            rate_limit: RateLimit::from_env(),
+            faults: Faults::from_env()?,
+            size: DatasetSize::from_env(),
+        };
+
+        config.rate_limit.validate()?;
+        config.size.validate()?;
+
+        Ok(config)
+    }
+}
+
+/// Changes of the config, the parts that are not set are kept as they are
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct ConfigUpdate {
+    pub delays: Option<Delays>,
+    pub rate_limit: Option<RateLimit>,
+    pub faults: Option<Faults>,
+    /// The data is generated again from the seed of the current data if the size changes.
+    /// The current data is replaced, so the changes made with the write routes
+    /// and the data loaded with `PUT /database` are lost
+    pub size: Option<DatasetSize>,
+}
+
+impl ConfigUpdate {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(delays) = &self.delays {
+            delays.validate()?;
+        }
+
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.validate()?;
+        }
+
+        if let Some(faults) = &self.faults {
+            faults.validate()?;
+        }
+
+        if let Some(size) = &self.size {
+            size.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Config, ConfigUpdate};
+    use crate::AppState;
+
+    fn update(value: serde_json::Value) -> ConfigUpdate {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_update_config() {
+        let state = AppState::new(1, Config::default());
+        let post = serde_json::to_value(state.db.post(1)).unwrap();
+
+        state
+            .update_config(update(json!({
+                "rateLimit": { "enabled": true, "burstSize": 5 },
+                "size": { "posts": 50 }
+            })))
+            .unwrap();
+
+        let config = state.config();
+        assert!(config.rate_limit.enabled);
+        assert_eq!(config.size.posts, 50);
+        assert_eq!(state.db.posts().len(), 50);
+        // the data is generated from the same seed
+        assert_eq!(serde_json::to_value(state.db.post(1)).unwrap(), post);
+
+        let invalid = update(json!({
+            "rateLimit": { "enabled": false },
+            "size": { "users": 0 }
+        }));
+        assert!(state.update_config(invalid).is_err());
+        assert_eq!(state.config(), config);
+
+        // the keys are in camelCase like the rest of the payloads
+        assert!(serde_json::from_value::<ConfigUpdate>(json!({ "rate_limit": {} })).is_err());
+    }
+}
@@ -1,17 +1,169 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::BTreeMap,
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
 
-use mock_json::mock;
-use serde_json::json;
+use fake::{
+    faker::{
+        address::en::ZipCode,
+        internet::en::{DomainSuffix, SafeEmail},
+        lorem::en::Sentence,
+        name::en::{FirstName, Name},
+        phone_number::en::PhoneNumber,
+    },
+    Fake,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::{PostData, UserData};
+use crate::{
+    utils::env_default, AddressData, AlbumData, CommentData, GeoData, NewPostData, NewUserData,
+    PhotoData, PostData, TodoData, UserData,
+};
+
+/// Full content of the database, used to save and restore the data
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Dataset {
+    /// Seed the data was generated from,
+    /// used when the data is regenerated with another size
+    #[serde(default)]
+    pub seed: u64,
+    pub users: Vec<UserData>,
+    pub posts: Vec<PostData>,
+    #[serde(default)]
+    pub comments: Vec<CommentData>,
+    #[serde(default)]
+    pub albums: Vec<AlbumData>,
+    #[serde(default)]
+    pub photos: Vec<PhotoData>,
+    #[serde(default)]
+    pub todos: Vec<TodoData>,
+}
+
+/// Number of generated entities of every kind
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatasetSize {
+    pub users: u32,
+    pub posts: u32,
+    pub comments: u32,
+    pub albums: u32,
+    pub photos: u32,
+    pub todos: u32,
+}
+
+impl Default for DatasetSize {
+    fn default() -> Self {
+        Self {
+            users: 10,
+            posts: 20,
+            comments: 100,
+            albums: 20,
+            photos: 100,
+            todos: 40,
+        }
+    }
+}
+
+impl DatasetSize {
+    /// Reads the size from `MOCK_SERVER_USERS`, `MOCK_SERVER_POSTS`,
+    /// `MOCK_SERVER_COMMENTS`, `MOCK_SERVER_ALBUMS`, `MOCK_SERVER_PHOTOS`
+    /// and `MOCK_SERVER_TODOS`, the default is used for missing ones
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            users: env_default("MOCK_SERVER_USERS", default.users),
+            posts: env_default("MOCK_SERVER_POSTS", default.posts),
+            comments: env_default("MOCK_SERVER_COMMENTS", default.comments),
+            albums: env_default("MOCK_SERVER_ALBUMS", default.albums),
+            photos: env_default("MOCK_SERVER_PHOTOS", default.photos),
+            todos: env_default("MOCK_SERVER_TODOS", default.todos),
+        }
+    }
+
+    /// Every entity should have a parent to reference
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        let references = [
+            ("posts", self.posts, "users", self.users),
+            ("albums", self.albums, "users", self.users),
+            ("todos", self.todos, "users", self.users),
+            ("comments", self.comments, "posts", self.posts),
+            ("photos", self.photos, "albums", self.albums),
+        ];
+
+        for (name, count, parent, parent_count) in references {
+            if count > 0 && parent_count == 0 {
+                anyhow::bail!("Cannot generate {name} without {parent}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Content of the database, it's replaced as a whole
+/// so readers never see tables of different datasets
+#[derive(Default)]
+pub(crate) struct Tables {
+    /// Seed and size of the last generated data
+    generated: (u64, DatasetSize),
+    pub(crate) users: BTreeMap<i64, UserData>,
+    pub(crate) posts: BTreeMap<i64, PostData>,
+    pub(crate) comments: BTreeMap<i64, CommentData>,
+    pub(crate) albums: BTreeMap<i64, AlbumData>,
+    pub(crate) photos: BTreeMap<i64, PhotoData>,
+    pub(crate) todos: BTreeMap<i64, TodoData>,
+}
+
+impl Tables {
+    /// Removes the post together with its comments
+    fn remove_post(&mut self, id: i64) -> Option<PostData> {
+        let post = self.posts.remove(&id)?;
+
+        self.comments.retain(|_, comment| comment.post_id != id);
+
+        Some(post)
+    }
+
+    /// Removes the user together with everything that references it,
+    /// so no entity is left with a `userId` of a missing user
+    fn remove_user(&mut self, id: i64) -> Option<UserData> {
+        let user = self.users.remove(&id)?;
+
+        let posts: Vec<_> = self
+            .posts
+            .values()
+            .filter(|post| post.user_id == id)
+            .map(|post| post.id)
+            .collect();
+
+        for post in posts {
+            self.remove_post(post);
+        }
+
+        let albums: Vec<_> = self
+            .albums
+            .values()
+            .filter(|album| album.user_id == id)
+            .map(|album| album.id)
+            .collect();
+
+        self.albums.retain(|_, album| album.user_id != id);
+        self.photos
+            .retain(|_, photo| !albums.contains(&photo.album_id));
+        self.todos.retain(|_, todo| todo.user_id != id);
+
+        Some(user)
+    }
+}
 
 /// Helper struct that is used to store the data
-/// for the responses
+/// for the responses. The data is kept sorted by id
+/// so lists are returned in the same order in every process
 pub struct Database {
-    user_template: serde_json::Value,
-    post_template: serde_json::Value,
-    users: Mutex<HashMap<i64, UserData>>,
-    posts: Mutex<HashMap<i64, PostData>>,
+    tables: RwLock<Tables>,
 }
 
 // JSON.parse in js converts values like 1.0 to integer
@@ -24,94 +176,532 @@ fn geo_add_fractional_part(val: &mut f64) {
     }
 }
 
+fn generate_user(rng: &mut StdRng, id: i64) -> UserData {
+    let username: String = FirstName().fake_with_rng(rng);
+    let domain: String = DomainSuffix().fake_with_rng(rng);
+
+    let mut geo = GeoData {
+        lat: round(rng.gen_range(-90.0..90.0)),
+        lng: round(rng.gen_range(-180.0..180.0)),
+    };
+    geo_add_fractional_part(&mut geo.lat);
+    geo_add_fractional_part(&mut geo.lng);
+
+    UserData {
+        id,
+        name: Name().fake_with_rng(rng),
+        phone: PhoneNumber().fake_with_rng(rng),
+        email: SafeEmail().fake_with_rng(rng),
+        website: format!("https://{}.{domain}", username.to_lowercase()),
+        username,
+        address: AddressData {
+            geo,
+            zipcode: ZipCode().fake_with_rng(rng),
+        },
+    }
+}
+
+fn generate_post(rng: &mut StdRng, id: i64, users: u32) -> PostData {
+    PostData {
+        id,
+        title: Sentence(2..6).fake_with_rng(rng),
+        user_id: rng.gen_range(1..=users.into()),
+        body: Sentence(5..15).fake_with_rng(rng),
+    }
+}
+
+fn generate_comment(rng: &mut StdRng, id: i64, posts: u32) -> CommentData {
+    CommentData {
+        id,
+        post_id: rng.gen_range(1..=posts.into()),
+        name: Sentence(2..5).fake_with_rng(rng),
+        email: SafeEmail().fake_with_rng(rng),
+        body: Sentence(5..15).fake_with_rng(rng),
+    }
+}
+
+fn generate_album(rng: &mut StdRng, id: i64, users: u32) -> AlbumData {
+    AlbumData {
+        id,
+        user_id: rng.gen_range(1..=users.into()),
+        title: Sentence(2..6).fake_with_rng(rng),
+    }
+}
+
+fn generate_photo(rng: &mut StdRng, id: i64, albums: u32) -> PhotoData {
+    let color: u32 = rng.gen_range(0..0x1000000);
+
+    PhotoData {
+        id,
+        album_id: rng.gen_range(1..=albums.into()),
+        title: Sentence(2..6).fake_with_rng(rng),
+        url: format!("https://via.placeholder.com/600/{color:06x}"),
+        thumbnail_url: format!("https://via.placeholder.com/150/{color:06x}"),
+    }
+}
+
+fn generate_todo(rng: &mut StdRng, id: i64, users: u32) -> TodoData {
+    TodoData {
+        id,
+        user_id: rng.gen_range(1..=users.into()),
+        title: Sentence(2..6).fake_with_rng(rng),
+        completed: rng.gen_bool(0.5),
+    }
+}
+
+/// Id after the largest one, ids of deleted entities are not reused
+/// unless they were the last ones
+fn next_id<T>(map: &BTreeMap<i64, T>) -> i64 {
+    map.keys().next_back().map_or(1, |id| id + 1)
+}
+
+/// Replaces the fields of the entity with the provided ones.
+/// Null values are ignored since GraphQL servers send them for omitted fields
+fn apply_patch<T: Serialize + DeserializeOwned>(
+    entity: &T,
+    patch: Map<String, Value>,
+) -> Result<T, anyhow::Error> {
+    let Value::Object(mut fields) = serde_json::to_value(entity)? else {
+        anyhow::bail!("Expected the entity to be an object");
+    };
+
+    for (key, value) in patch {
+        if key == "id" {
+            anyhow::bail!("Field `id` cannot be changed");
+        }
+
+        if !value.is_null() {
+            fields.insert(key, value);
+        }
+    }
+
+    Ok(serde_json::from_value(Value::Object(fields))?)
+}
+
+/// Keeps 4 digits after the point
+fn round(val: f64) -> f64 {
+    (val * 10000.0).round() / 10000.0
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Database {
-    /// Initialize the database with random data
+    /// Initialize the empty database
     pub fn new() -> Self {
         Self {
-            user_template: json!({
-                    "id": "@Number|1~10",
-                    "name": "@Name",
-                    "username": "@FirstName",
-                    "email": "@Email",
-                    "phone": "@Phone",
-                    "website": "@Url",
-                    "address": {
-                        "zipcode": "@Zip",
-                        "geo": {
-                            "lat": "@Float|4|-90~90",
-                            "lng": "@Float|4|-180~180",
-                        }
-                    }
-            }),
-            post_template: json!({
-                "id": "@Number|1~10",
-                "userId": "@Number|1~10",
-                "title": "@Title",
-                "body": "@Sentence",
-            }),
-            users: Mutex::new(HashMap::new()),
-            posts: Mutex::new(HashMap::new()),
+            tables: RwLock::new(Tables::default()),
         }
     }
 
-    /// Used to reset the database and generate new data
-    pub fn reset(&self) -> Result<(), anyhow::Error> {
-        // clear the previous data from database.
-        self.users
-            .lock()
-            .map_err(|_| anyhow::anyhow!("Failed to clear users."))?
-            .clear();
-        self.posts
-            .lock()
-            .map_err(|_| anyhow::anyhow!("Failed to clear posts."))?
-            .clear();
-
-        // Generate and store users
-        let mut users_map = self
-            .users
-            .lock()
-            .map_err(|_| anyhow::anyhow!("Failed to access users"))?;
-        for id in 1..=10 {
-            let mut user: UserData = serde_json::from_value(mock(&self.user_template))
-                .map_err(|_| anyhow::anyhow!("Failed to generate user"))?;
-            user.id = id;
-            geo_add_fractional_part(&mut user.address.geo.lat);
-            geo_add_fractional_part(&mut user.address.geo.lng);
-            users_map.insert(id, user);
-        }
+    /// Used to go through the tables without copying them,
+    /// the writes wait until the guard is dropped
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap()
+    }
 
-        // Generate and store posts
-        let mut posts_map = self
-            .posts
-            .lock()
-            .map_err(|_| anyhow::anyhow!("Failed to access posts"))?;
-        for id in 1..=20 {
-            let mut post: PostData = serde_json::from_value(mock(&self.post_template))
-                .map_err(|_| anyhow::anyhow!("Failed to generate user"))?;
-            post.id = id;
-            posts_map.insert(id, post);
+    fn write(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap()
+    }
+
+    /// Used to reset the database and generate new data of the given size.
+    /// The same seed and size always generate the same data
+    pub fn reset(&self, seed: u64, size: DatasetSize) -> Result<(), anyhow::Error> {
+        size.validate()?;
+
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        // foreign keys point to the ids of the entities generated here
+        let users = (1..=size.users.into())
+            .map(|id| (id, generate_user(&mut rng, id)))
+            .collect();
+        let posts = (1..=size.posts.into())
+            .map(|id| (id, generate_post(&mut rng, id, size.users)))
+            .collect();
+        let comments = (1..=size.comments.into())
+            .map(|id| (id, generate_comment(&mut rng, id, size.posts)))
+            .collect();
+        let albums = (1..=size.albums.into())
+            .map(|id| (id, generate_album(&mut rng, id, size.users)))
+            .collect();
+        let photos = (1..=size.photos.into())
+            .map(|id| (id, generate_photo(&mut rng, id, size.albums)))
+            .collect();
+        let todos = (1..=size.todos.into())
+            .map(|id| (id, generate_todo(&mut rng, id, size.users)))
+            .collect();
+
+        *self.write() = Tables {
+            generated: (seed, size),
+            users,
+            posts,
+            comments,
+            albums,
+            photos,
+            todos,
+        };
+
+        Ok(())
+    }
+
+    /// Used to get the seed of the last generated data
+    pub fn seed(&self) -> u64 {
+        self.read().generated.0
+    }
+
+    /// Used to get the size of the last generated data
+    pub fn size(&self) -> DatasetSize {
+        self.read().generated.1
+    }
+
+    /// Used to get the whole data
+    pub fn dump(&self) -> Dataset {
+        let tables = self.read();
+
+        Dataset {
+            seed: tables.generated.0,
+            users: tables.users.values().cloned().collect(),
+            posts: tables.posts.values().cloned().collect(),
+            comments: tables.comments.values().cloned().collect(),
+            albums: tables.albums.values().cloned().collect(),
+            photos: tables.photos.values().cloned().collect(),
+            todos: tables.todos.values().cloned().collect(),
         }
+    }
+
+    /// Used to replace the data with the provided one
+    pub fn load(&self, dataset: Dataset) -> Result<(), anyhow::Error> {
+        let size = DatasetSize {
+            users: dataset.users.len() as u32,
+            posts: dataset.posts.len() as u32,
+            comments: dataset.comments.len() as u32,
+            albums: dataset.albums.len() as u32,
+            photos: dataset.photos.len() as u32,
+            todos: dataset.todos.len() as u32,
+        };
+
+        let tables = Tables {
+            generated: (dataset.seed, size),
+            users: dataset
+                .users
+                .into_iter()
+                .map(|user| (user.id, user))
+                .collect(),
+            posts: dataset
+                .posts
+                .into_iter()
+                .map(|post| (post.id, post))
+                .collect(),
+            comments: dataset
+                .comments
+                .into_iter()
+                .map(|comment| (comment.id, comment))
+                .collect(),
+            albums: dataset
+                .albums
+                .into_iter()
+                .map(|album| (album.id, album))
+                .collect(),
+            photos: dataset
+                .photos
+                .into_iter()
+                .map(|photo| (photo.id, photo))
+                .collect(),
+            todos: dataset
+                .todos
+                .into_iter()
+                .map(|todo| (todo.id, todo))
+                .collect(),
+        };
+
+        *self.write() = tables;
 
         Ok(())
     }
 
     /// Used to get all posts
     pub fn posts(&self) -> Vec<PostData> {
-        self.posts.lock().unwrap().values().cloned().collect()
+        self.read().posts.values().cloned().collect()
     }
 
     /// Used to get a post
     pub fn post(&self, id: i64) -> Option<PostData> {
-        self.posts.lock().unwrap().get(&id).cloned()
+        self.read().posts.get(&id).cloned()
     }
 
     /// Used to get all users
     pub fn users(&self) -> Vec<UserData> {
-        self.users.lock().unwrap().values().cloned().collect()
+        self.read().users.values().cloned().collect()
     }
 
     /// Used to get a user
     pub fn user(&self, id: i64) -> Option<UserData> {
-        self.users.lock().unwrap().get(&id).cloned()
+        self.read().users.get(&id).cloned()
+    }
+
+    /// Used to get all comments
+    pub fn comments(&self) -> Vec<CommentData> {
+        self.read().comments.values().cloned().collect()
+    }
+
+    /// Used to get a comment
+    pub fn comment(&self, id: i64) -> Option<CommentData> {
+        self.read().comments.get(&id).cloned()
+    }
+
+    /// Used to get all albums
+    pub fn albums(&self) -> Vec<AlbumData> {
+        self.read().albums.values().cloned().collect()
+    }
+
+    /// Used to get an album
+    pub fn album(&self, id: i64) -> Option<AlbumData> {
+        self.read().albums.get(&id).cloned()
+    }
+
+    /// Used to get all photos
+    pub fn photos(&self) -> Vec<PhotoData> {
+        self.read().photos.values().cloned().collect()
+    }
+
+    /// Used to get a photo
+    pub fn photo(&self, id: i64) -> Option<PhotoData> {
+        self.read().photos.get(&id).cloned()
+    }
+
+    /// Used to get all todos
+    pub fn todos(&self) -> Vec<TodoData> {
+        self.read().todos.values().cloned().collect()
+    }
+
+    /// Used to get a todo
+    pub fn todo(&self, id: i64) -> Option<TodoData> {
+        self.read().todos.get(&id).cloned()
+    }
+
+    /// Used to create a post with the next id
+    pub fn create_post(&self, post: NewPostData) -> PostData {
+        let mut tables = self.write();
+        let posts = &mut tables.posts;
+        let post = post.with_id(next_id(posts));
+
+        posts.insert(post.id, post.clone());
+
+        post
+    }
+
+    /// Used to replace a post, returns `None` if it doesn't exist
+    pub fn update_post(&self, id: i64, post: NewPostData) -> Option<PostData> {
+        let mut tables = self.write();
+        let posts = &mut tables.posts;
+        let stored = posts.get_mut(&id)?;
+
+        *stored = post.with_id(id);
+
+        Some(stored.clone())
+    }
+
+    /// Used to change some fields of a post, returns `None` if it doesn't exist
+    pub fn patch_post(
+        &self,
+        id: i64,
+        patch: Map<String, Value>,
+    ) -> Result<Option<PostData>, anyhow::Error> {
+        let mut tables = self.write();
+        let posts = &mut tables.posts;
+        let Some(stored) = posts.get_mut(&id) else {
+            return Ok(None);
+        };
+
+        *stored = apply_patch(stored, patch)?;
+
+        Ok(Some(stored.clone()))
+    }
+
+    /// Used to delete a post with its comments, returns the deleted post
+    pub fn delete_post(&self, id: i64) -> Option<PostData> {
+        self.write().remove_post(id)
+    }
+
+    /// Used to create a user with the next id
+    pub fn create_user(&self, user: NewUserData) -> UserData {
+        let mut tables = self.write();
+        let users = &mut tables.users;
+        let user = user.with_id(next_id(users));
+
+        users.insert(user.id, user.clone());
+
+        user
+    }
+
+    /// Used to replace a user, returns `None` if it doesn't exist
+    pub fn update_user(&self, id: i64, user: NewUserData) -> Option<UserData> {
+        let mut tables = self.write();
+        let users = &mut tables.users;
+        let stored = users.get_mut(&id)?;
+
+        *stored = user.with_id(id);
+
+        Some(stored.clone())
+    }
+
+    /// Used to change some fields of a user, returns `None` if it doesn't exist
+    pub fn patch_user(
+        &self,
+        id: i64,
+        patch: Map<String, Value>,
+    ) -> Result<Option<UserData>, anyhow::Error> {
+        let mut tables = self.write();
+        let users = &mut tables.users;
+        let Some(stored) = users.get_mut(&id) else {
+            return Ok(None);
+        };
+
+        *stored = apply_patch(stored, patch)?;
+
+        Ok(Some(stored.clone()))
+    }
+
+    /// Used to delete a user with the posts, albums and todos of the user,
+    /// returns the deleted user
+    pub fn delete_user(&self, id: i64) -> Option<UserData> {
+        self.write().remove_user(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Database, DatasetSize};
+
+    #[test]
+    fn test_reset_is_deterministic() {
+        let first = Database::new();
+        let second = Database::new();
+
+        first.reset(42, DatasetSize::default()).unwrap();
+        second.reset(42, DatasetSize::default()).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(first.dump()).unwrap(),
+            serde_json::to_value(second.dump()).unwrap()
+        );
+
+        second.reset(43, DatasetSize::default()).unwrap();
+
+        assert_ne!(
+            serde_json::to_value(first.dump()).unwrap(),
+            serde_json::to_value(second.dump()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_posts() {
+        let db = Database::new();
+
+        db.reset(42, DatasetSize::default()).unwrap();
+
+        let post = db.create_post(
+            serde_json::from_value(json!({ "title": "New", "userId": 1, "body": "Text" })).unwrap(),
+        );
+
+        assert_eq!(post.id, 21);
+        assert_eq!(db.post(21).unwrap().title, "New");
+
+        let patch = json!({ "title": "Changed", "body": null });
+        let post = db
+            .patch_post(21, patch.as_object().unwrap().clone())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(post.title, "Changed");
+        assert_eq!(post.body, "Text");
+
+        let patch = json!({ "userId": "one" });
+        assert!(db
+            .patch_post(21, patch.as_object().unwrap().clone())
+            .is_err());
+
+        assert_eq!(db.delete_post(21).unwrap().id, 21);
+        assert!(db.post(21).is_none());
+        assert!(db.delete_post(21).is_none());
+    }
+
+    #[test]
+    fn test_delete_user() {
+        let db = Database::new();
+
+        db.reset(42, DatasetSize::default()).unwrap();
+
+        let posts: Vec<_> = db
+            .posts()
+            .into_iter()
+            .filter(|post| post.user_id == 1)
+            .map(|post| post.id)
+            .collect();
+        let albums: Vec<_> = db
+            .albums()
+            .into_iter()
+            .filter(|album| album.user_id == 1)
+            .map(|album| album.id)
+            .collect();
+
+        assert!(!posts.is_empty() && !albums.is_empty());
+        assert_eq!(db.delete_user(1).unwrap().id, 1);
+        assert!(db.user(1).is_none());
+        assert!(db.delete_user(1).is_none());
+
+        assert!(db.posts().iter().all(|post| post.user_id != 1));
+        assert!(db.albums().iter().all(|album| album.user_id != 1));
+        assert!(db.todos().iter().all(|todo| todo.user_id != 1));
+        assert!(db
+            .comments()
+            .iter()
+            .all(|comment| !posts.contains(&comment.post_id)));
+        assert!(db
+            .photos()
+            .iter()
+            .all(|photo| !albums.contains(&photo.album_id)));
+        assert_eq!(db.users().len(), 9);
+    }
+
+    #[test]
+    fn test_dataset_size() {
+        let db = Database::new();
+        let size = DatasetSize {
+            users: 3,
+            posts: 500,
+            ..Default::default()
+        };
+
+        db.reset(1, size).unwrap();
+
+        assert_eq!(db.size(), size);
+        assert_eq!(db.users().len(), 3);
+        assert_eq!(db.posts().len(), 500);
+        assert!(db
+            .posts()
+            .iter()
+            .all(|post| (1..=3).contains(&post.user_id)));
+        assert!(db.comments().iter().all(|comment| comment.post_id <= 500));
+
+        let invalid = DatasetSize {
+            users: 0,
+            ..Default::default()
+        };
+
+        assert!(db.reset(1, invalid).is_err());
+        assert_eq!(db.size(), size);
+
+        let loaded = Database::new();
+
+        loaded.load(db.dump()).unwrap();
+
+        assert_eq!(loaded.seed(), 1);
+        assert_eq!(loaded.size(), size);
     }
 }
@@ -0,0 +1,311 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use rand::Rng;
+use rand_distr::{Distribution as _, LogNormal, Normal, Pareto};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    utils::{env_default, read_json_file},
+    AppState,
+};
+
+/// Distribution of the delay, all values are in milliseconds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase",
+    deny_unknown_fields
+)]
+pub enum Distribution {
+    /// Always the same delay
+    Fixed { ms: f64 },
+    /// Any delay between `min` and `max` with the same probability
+    Uniform { min: f64, max: f64 },
+    /// Delays around the `mean`, negative samples are treated as zero
+    Normal { mean: f64, std_dev: f64 },
+    /// Long tail, most delays are close to `scale`.
+    /// The lower the `shape` the longer the tail
+    Pareto { scale: f64, shape: f64 },
+    /// Long tail, half of the delays are below the `median`.
+    /// `sigma` is the standard deviation of the logarithm of the delay
+    LogNormal { median: f64, sigma: f64 },
+}
+
+impl Distribution {
+    fn validate(&self) -> anyhow::Result<()> {
+        match *self {
+            Distribution::Fixed { ms } => check_non_negative("ms", ms)?,
+            Distribution::Uniform { min, max } => {
+                check_non_negative("min", min)?;
+                check_non_negative("max", max)?;
+
+                if min > max {
+                    anyhow::bail!("`min` {min} is greater than `max` {max}");
+                }
+            }
+            Distribution::Normal { mean, std_dev } => {
+                Normal::new(mean, std_dev).context("Invalid normal distribution")?;
+            }
+            Distribution::Pareto { scale, shape } => {
+                Pareto::new(scale, shape).context("Invalid pareto distribution")?;
+            }
+            Distribution::LogNormal { median, sigma } => {
+                check_non_negative("median", median)?;
+                LogNormal::new(median.ln(), sigma).context("Invalid lognormal distribution")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delay in milliseconds, the distribution has to be validated
+    fn sample(&self, rng: &mut impl Rng) -> f64 {
+        match *self {
+            Distribution::Fixed { ms } => ms,
+            Distribution::Uniform { min, max } => rng.gen_range(min..=max),
+            Distribution::Normal { mean, std_dev } => {
+                Normal::new(mean, std_dev).unwrap().sample(rng)
+            }
+            Distribution::Pareto { scale, shape } => Pareto::new(scale, shape).unwrap().sample(rng),
+            Distribution::LogNormal { median, sigma } => {
+                LogNormal::new(median.ln(), sigma).unwrap().sample(rng)
+            }
+        }
+    }
+}
+
+fn check_non_negative(name: &str, value: f64) -> anyhow::Result<()> {
+    if !(value.is_finite() && value >= 0.0) {
+        anyhow::bail!("`{name}` should be a non-negative number, got {value}");
+    }
+
+    Ok(())
+}
+
+/// Delay of the responses of a route
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DelayProfile {
+    pub distribution: Distribution,
+    /// Random value up to `jitter` ms is added to or subtracted from every delay
+    #[serde(default)]
+    pub jitter: f64,
+    /// Upper bound of the delay in ms, limits long tails
+    pub max: Option<f64>,
+}
+
+impl DelayProfile {
+    /// Always the same delay in milliseconds
+    pub fn fixed(ms: u64) -> Self {
+        Self {
+            distribution: Distribution::Fixed { ms: ms as f64 },
+            jitter: 0.0,
+            max: None,
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.distribution.validate()?;
+        check_non_negative("jitter", self.jitter)?;
+
+        if let Some(max) = self.max {
+            check_non_negative("max", max)?;
+        }
+
+        Ok(())
+    }
+
+    /// Random delay from the distribution with the jitter applied
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        let mut ms = self.distribution.sample(rng);
+
+        if self.jitter > 0.0 {
+            ms += rng.gen_range(-self.jitter..=self.jitter);
+        }
+
+        if let Some(max) = self.max {
+            ms = ms.min(max);
+        }
+
+        // long tails can produce huge values, while NaN becomes zero
+        Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).unwrap_or(Duration::MAX)
+    }
+}
+
+/// Delays of the responses by route, used to simulate a real world upstream
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Delays {
+    /// Delay of the routes without their own profile
+    #[serde(default = "Delays::default_profile")]
+    pub default: DelayProfile,
+    /// Profiles by method and route, e.g. `GET /users/:user_id`
+    #[serde(default)]
+    pub routes: BTreeMap<String, DelayProfile>,
+}
+
+impl Default for Delays {
+    fn default() -> Self {
+        Self {
+            default: Self::default_profile(),
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+impl Delays {
+    fn default_profile() -> DelayProfile {
+        DelayProfile::fixed(5)
+    }
+
+    /// Reads the profiles from the json file at `MOCK_SERVER_DELAY_CONFIG`.
+    /// Without it every route has the fixed delay of `MOCK_SERVER_DELAY` ms
+    pub fn from_env() -> anyhow::Result<Self> {
+        let Ok(path) = std::env::var("MOCK_SERVER_DELAY_CONFIG") else {
+            return Ok(Self {
+                default: DelayProfile::fixed(env_default("MOCK_SERVER_DELAY", 5)),
+                routes: BTreeMap::new(),
+            });
+        };
+
+        let delays: Self = read_json_file(path)?;
+
+        delays.validate()?;
+
+        Ok(delays)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.default.validate().context("Invalid default profile")?;
+
+        for (route, profile) in &self.routes {
+            profile
+                .validate()
+                .with_context(|| format!("Invalid profile of `{route}`"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Profile of the route, e.g. `GET /users/:user_id`
+    pub fn profile(&self, route: &str) -> &DelayProfile {
+        self.routes.get(route).unwrap_or(&self.default)
+    }
+}
+
+/// Middleware that delays the requests before they are handled
+/// according to the profile of the route
+pub async fn delay(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let delay = {
+        let delays = state.delays.read().unwrap();
+
+        match request.extensions().get::<MatchedPath>() {
+            Some(path) => {
+                let route = format!("{} {}", request.method(), path.as_str());
+
+                delays.profile(&route).sample(&mut rand::thread_rng())
+            }
+            None => delays.default.sample(&mut rand::thread_rng()),
+        }
+    };
+
+    tokio::time::sleep(delay).await;
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::{rngs::StdRng, SeedableRng};
+    use serde_json::json;
+
+    use super::{DelayProfile, Delays};
+
+    fn profile(value: serde_json::Value) -> DelayProfile {
+        let profile: DelayProfile = serde_json::from_value(value).unwrap();
+
+        profile.validate().unwrap();
+
+        profile
+    }
+
+    #[test]
+    fn test_delay_profiles() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let fixed = profile(json!({ "distribution": { "type": "fixed", "ms": 5 } }));
+        assert_eq!(fixed.sample(&mut rng), Duration::from_millis(5));
+
+        let uniform = profile(json!({
+            "distribution": { "type": "uniform", "min": 10, "max": 20 },
+            "jitter": 2
+        }));
+        for _ in 0..100 {
+            let delay = uniform.sample(&mut rng);
+            assert!(delay >= Duration::from_millis(8) && delay <= Duration::from_millis(22));
+        }
+
+        let pareto = profile(json!({
+            "distribution": { "type": "pareto", "scale": 5, "shape": 1.1 },
+            "max": 100
+        }));
+        let delays: Vec<_> = (0..1000).map(|_| pareto.sample(&mut rng)).collect();
+        assert!(delays
+            .iter()
+            .all(|delay| *delay >= Duration::from_millis(5)));
+        assert!(delays
+            .iter()
+            .all(|delay| *delay <= Duration::from_millis(100)));
+        assert!(delays
+            .iter()
+            .any(|delay| *delay > Duration::from_millis(50)));
+
+        let normal = profile(json!({
+            "distribution": { "type": "normal", "mean": 1, "stdDev": 10 }
+        }));
+        assert!((0..100).all(|_| normal.sample(&mut rng) <= Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_invalid_delays() {
+        let parse = |value| serde_json::from_value::<Delays>(value).map_err(anyhow::Error::from);
+
+        let delays = parse(json!({
+            "routes": {
+                "GET /users/:user_id": {
+                    "distribution": { "type": "lognormal", "median": 5, "sigma": 0.5 }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(delays.profile("GET /posts"), &DelayProfile::fixed(5));
+        assert!(delays.validate().is_ok());
+
+        assert!(parse(json!({ "default": { "distribution": { "type": "fixed" } } })).is_err());
+        assert!(parse(
+            json!({ "default": { "distribution": { "type": "fixed", "ms": 1, "jiter": 1 } } })
+        )
+        .is_err());
+
+        let invalid = parse(json!({
+            "default": { "distribution": { "type": "uniform", "min": 5, "max": 1 } },
+        }))
+        .unwrap();
+        assert!(invalid.validate().is_err());
+
+        let invalid = parse(json!({
+            "default": { "distribution": { "type": "pareto", "scale": 0, "shape": 1 } },
+        }))
+        .unwrap();
+        assert!(invalid.validate().is_err());
+    }
+}
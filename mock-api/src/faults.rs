@@ -0,0 +1,264 @@
+use std::{collections::BTreeMap, io, sync::Arc, time::Duration};
+
+use anyhow::Context;
+use axum::{
+    body::{Body, Bytes},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::read_json_file, AppState};
+
+/// Failure of the upstream simulated instead of a normal response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Fault {
+    /// Error response with the status, e.g. `500` or `503`
+    Status { status: u16 },
+    /// The connection is closed without any response
+    Reset,
+    /// The response is sent only after `ms` milliseconds
+    Hang {
+        #[serde(default = "Fault::default_hang")]
+        ms: u64,
+    },
+    /// Only the first half of the json body is sent
+    Truncated,
+    /// The json body is broken with single quotes instead of double ones
+    Malformed,
+    /// Html page instead of json, like the ones of misconfigured proxies
+    ContentType,
+}
+
+impl Fault {
+    fn default_hang() -> u64 {
+        60_000
+    }
+}
+
+/// Fault that happens with the probability of `rate`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FaultRule {
+    /// Probability from `0` to `1`
+    pub rate: f64,
+    pub fault: Fault,
+}
+
+/// Faults injected into the responses of the data routes
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Faults {
+    /// Faults of the routes without their own rules
+    pub default: Vec<FaultRule>,
+    /// Rules by method and route, e.g. `GET /users/:user_id`
+    pub routes: BTreeMap<String, Vec<FaultRule>>,
+}
+
+impl Faults {
+    /// Reads the faults from the json file at `MOCK_SERVER_FAULT_CONFIG`,
+    /// there are no faults without it
+    pub fn from_env() -> anyhow::Result<Self> {
+        let Ok(path) = std::env::var("MOCK_SERVER_FAULT_CONFIG") else {
+            return Ok(Self::default());
+        };
+
+        let faults: Self = read_json_file(path)?;
+
+        faults.validate()?;
+
+        Ok(faults)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        validate_rules(&self.default).context("Invalid default faults")?;
+
+        for (route, rules) in &self.routes {
+            validate_rules(rules).with_context(|| format!("Invalid faults of `{route}`"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Randomly picks one of the faults of the route, if any
+    pub fn pick(&self, route: &str, rng: &mut impl Rng) -> Option<&Fault> {
+        let rules = self.routes.get(route).unwrap_or(&self.default);
+
+        if rules.is_empty() {
+            return None;
+        }
+
+        let mut value = rng.gen::<f64>();
+
+        for rule in rules {
+            if value < rule.rate {
+                return Some(&rule.fault);
+            }
+
+            value -= rule.rate;
+        }
+
+        None
+    }
+}
+
+fn validate_rules(rules: &[FaultRule]) -> anyhow::Result<()> {
+    for rule in rules {
+        if !(0.0..=1.0).contains(&rule.rate) {
+            anyhow::bail!("`rate` should be between 0 and 1, got {}", rule.rate);
+        }
+
+        if let Fault::Status { status } = rule.fault {
+            if !(400..=599).contains(&status) {
+                anyhow::bail!("`status` should be an error status, got {status}");
+            }
+        }
+    }
+
+    let total: f64 = rules.iter().map(|rule| rule.rate).sum();
+
+    // allow the rounding errors of rates like `0.1 + 0.2 + 0.7`
+    if total > 1.0 + 1e-9 {
+        anyhow::bail!("Sum of the rates should be at most 1, got {total}");
+    }
+
+    Ok(())
+}
+
+/// Middleware that replaces the responses of the data routes with the faults
+pub async fn inject_faults(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let fault = request.extensions().get::<MatchedPath>().and_then(|path| {
+        let route = format!("{} {}", request.method(), path.as_str());
+
+        state
+            .faults
+            .read()
+            .unwrap()
+            .pick(&route, &mut rand::thread_rng())
+            .cloned()
+    });
+
+    let Some(fault) = fault else {
+        return next.run(request).await;
+    };
+
+    match fault {
+        Fault::Status { status } => (
+            StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            "Injected fault",
+        )
+            .into_response(),
+        Fault::Reset => {
+            // hyper closes the connection when the body fails before anything is sent
+            let body = futures_util::stream::once(async {
+                Err::<Bytes, _>(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "Injected fault",
+                ))
+            });
+
+            Body::from_stream(body).into_response()
+        }
+        Fault::Hang { ms } => {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+
+            next.run(request).await
+        }
+        Fault::Truncated => {
+            map_body(next.run(request).await, |body| body.slice(..body.len() / 2)).await
+        }
+        Fault::Malformed => {
+            map_body(next.run(request).await, |body| {
+                body.iter()
+                    .map(|byte| if *byte == b'"' { b'\'' } else { *byte })
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+            .await
+        }
+        Fault::ContentType => (
+            [(header::CONTENT_TYPE, HeaderValue::from_static("text/html"))],
+            "<html><body><h1>Service is temporarily unavailable</h1></body></html>",
+        )
+            .into_response(),
+    }
+}
+
+/// Replaces the body of the response keeping the status and headers
+async fn map_body(response: Response, f: impl FnOnce(Bytes) -> Bytes) -> Response {
+    let (mut parts, body) = response.into_parts();
+
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => f(body),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+    use serde_json::json;
+
+    use super::{Fault, Faults};
+
+    #[test]
+    fn test_pick_faults() {
+        let faults: Faults = serde_json::from_value(json!({
+            "default": [{ "rate": 0.5, "fault": { "type": "status", "status": 503 } }],
+            "routes": {
+                "GET /users/:user_id": [
+                    { "rate": 0.2, "fault": { "type": "reset" } },
+                    { "rate": 0.3, "fault": { "type": "hang" } }
+                ],
+                "GET /posts": []
+            }
+        }))
+        .unwrap();
+        faults.validate().unwrap();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut count = |route: &str, fault: &Fault| {
+            (0..10000)
+                .filter(|_| faults.pick(route, &mut rng) == Some(fault))
+                .count()
+        };
+
+        let status = Fault::Status { status: 503 };
+        assert!((4500..5500).contains(&count("GET /users", &status)));
+        assert!((1500..2500).contains(&count("GET /users/:user_id", &Fault::Reset)));
+        assert!((2500..3500).contains(&count("GET /users/:user_id", &Fault::Hang { ms: 60_000 })));
+        assert_eq!(count("GET /posts", &status), 0);
+    }
+
+    #[test]
+    fn test_invalid_faults() {
+        let invalid = [
+            json!({ "default": [{ "rate": 1.5, "fault": { "type": "reset" } }] }),
+            json!({ "default": [{ "rate": 0.5, "fault": { "type": "status", "status": 200 } }] }),
+            json!({ "default": [
+                { "rate": 0.6, "fault": { "type": "reset" } },
+                { "rate": 0.6, "fault": { "type": "truncated" } }
+            ] }),
+        ];
+
+        for faults in invalid {
+            let faults: Faults = serde_json::from_value(faults).unwrap();
+
+            assert!(faults.validate().is_err());
+        }
+
+        assert!(serde_json::from_value::<Faults>(json!({ "default": [{ "rate": 1 }] })).is_err());
+    }
+}
@@ -1,21 +1,81 @@
+use std::sync::RwLock;
+
 use axum::{http::StatusCode, response::IntoResponse};
+use config::{Config, ConfigUpdate};
 use database::Database;
+use delay::Delays;
+use faults::Faults;
+use rate_limit::RateLimiter;
 use serde::{Deserialize, Serialize};
+use stats::RequestStats;
 
+pub mod config;
 pub mod database;
+pub mod delay;
+pub mod faults;
+pub mod list;
+pub mod rate_limit;
 pub mod routes;
+pub mod stats;
 pub mod utils;
 
-/// Represents the application state
+/// Represents the application state.
+/// Everything except the data and the stats is a part of the `Config`
 pub struct AppState {
     pub db: Database,
+    pub stats: RequestStats,
+    pub delays: RwLock<Delays>,
+    pub rate_limiter: RwLock<RateLimiter>,
+    pub faults: RwLock<Faults>,
 }
 
-impl Default for AppState {
-    fn default() -> Self {
+impl AppState {
+    /// Initialize the state with data of the configured size generated from the seed
+    pub fn new(seed: u64, config: Config) -> Self {
         let db = Database::new();
-        let _ = db.reset().unwrap();
-        Self { db }
+        db.reset(seed, config.size).unwrap();
+        Self {
+            db,
+            stats: RequestStats::default(),
+            delays: RwLock::new(config.delays),
+            rate_limiter: RwLock::new(RateLimiter::new(config.rate_limit)),
+            faults: RwLock::new(config.faults),
+        }
+    }
+
+    /// Used to get the current config
+    pub fn config(&self) -> Config {
+        Config {
+            delays: self.delays.read().unwrap().clone(),
+            rate_limit: self.rate_limiter.read().unwrap().limit(),
+            faults: self.faults.read().unwrap().clone(),
+            size: self.db.size(),
+        }
+    }
+
+    /// Used to change the config at runtime, nothing is changed if the update is invalid
+    pub fn update_config(&self, update: ConfigUpdate) -> anyhow::Result<()> {
+        update.validate()?;
+
+        if let Some(delays) = update.delays {
+            *self.delays.write().unwrap() = delays;
+        }
+
+        if let Some(rate_limit) = update.rate_limit {
+            *self.rate_limiter.write().unwrap() = RateLimiter::new(rate_limit);
+        }
+
+        if let Some(faults) = update.faults {
+            *self.faults.write().unwrap() = faults;
+        }
+
+        if let Some(size) = update.size {
+            if size != self.db.size() {
+                self.db.reset(self.db.seed(), size)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -30,6 +90,31 @@ pub struct UserData {
     pub address: AddressData,
 }
 
+/// Body of the requests that create or replace a user
+#[derive(Deserialize, Debug)]
+pub struct NewUserData {
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+    pub username: String,
+    pub website: String,
+    pub address: AddressData,
+}
+
+impl NewUserData {
+    pub fn with_id(self, id: i64) -> UserData {
+        UserData {
+            id,
+            name: self.name,
+            phone: self.phone,
+            email: self.email,
+            username: self.username,
+            website: self.website,
+            address: self.address,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct AddressData {
     pub geo: GeoData,
@@ -51,10 +136,70 @@ pub struct PostData {
     pub body: String,
 }
 
+/// Body of the requests that create or replace a post
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPostData {
+    pub title: String,
+    pub user_id: i64,
+    pub body: String,
+}
+
+impl NewPostData {
+    pub fn with_id(self, id: i64) -> PostData {
+        PostData {
+            id,
+            title: self.title,
+            user_id: self.user_id,
+            body: self.body,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentData {
+    pub id: i64,
+    pub post_id: i64,
+    pub name: String,
+    pub email: String,
+    pub body: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumData {
+    pub id: i64,
+    pub user_id: i64,
+    pub title: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PhotoData {
+    pub id: i64,
+    pub album_id: i64,
+    pub title: String,
+    pub url: String,
+    pub thumbnail_url: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoData {
+    pub id: i64,
+    pub user_id: i64,
+    pub title: String,
+    pub completed: bool,
+}
+
 /// Custom error types for the application.
+#[derive(Debug)]
 pub enum AppError {
     /// Error indicating that a requested resource was not found.
     NotFound(String),
+    /// Error indicating that the request body is not valid.
+    BadRequest(String),
     /// Error indicating an internal server error occurred.
     InternalServerError(String),
 }
@@ -66,6 +211,7 @@ impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
         match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg).into_response(),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg).into_response(),
             AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response(),
         }
     }
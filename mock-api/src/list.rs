@@ -0,0 +1,438 @@
+use std::cmp::Ordering;
+
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{AlbumData, AppError, CommentData, PhotoData, PostData, TodoData, UserData};
+
+/// Number of items on a page when `_page` is set without `_limit`
+const DEFAULT_PAGE_LIMIT: usize = 10;
+
+const TOTAL_COUNT: HeaderName = HeaderName::from_static("x-total-count");
+
+/// jsonplaceholder-style parameters of the list routes:
+/// `_start`, `_end`, `_limit`, `_page`, `_sort`, `_order`
+/// and filters by fields like `userId=3` or `address.zipcode=12345`.
+/// Filters by fields the items don't have are ignored
+#[derive(Default, Debug)]
+pub struct ListParams {
+    /// Fields with the accepted values, an item matches if the field
+    /// is equal to any of the values, e.g. `/users?id=1&id=2`
+    filters: Vec<(String, Vec<String>)>,
+    sort: Option<String>,
+    descending: bool,
+    start: Option<usize>,
+    end: Option<usize>,
+    limit: Option<usize>,
+    page: Option<usize>,
+}
+
+impl ListParams {
+    pub fn parse(params: Vec<(String, String)>) -> Result<Self, AppError> {
+        let mut list = ListParams::default();
+
+        for (key, value) in params {
+            match key.as_str() {
+                "_start" => list.start = Some(parse_number(&key, &value)?),
+                "_end" => list.end = Some(parse_number(&key, &value)?),
+                "_limit" => list.limit = Some(parse_number(&key, &value)?),
+                "_page" => match parse_number(&key, &value)? {
+                    0 => return Err(AppError::BadRequest("`_page` starts from 1".to_string())),
+                    page => list.page = Some(page),
+                },
+                "_sort" => list.sort = Some(value),
+                "_order" => {
+                    list.descending = match value.as_str() {
+                        "asc" => false,
+                        "desc" => true,
+                        _ => {
+                            return Err(AppError::BadRequest(format!(
+                                "Invalid `_order` {value}, expected `asc` or `desc`"
+                            )))
+                        }
+                    }
+                }
+                _ => match list.filters.iter_mut().find(|(field, _)| *field == key) {
+                    Some((_, values)) => values.push(value),
+                    None => list.filters.push((key, vec![value])),
+                },
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Filters, sorts and slices the items that are sorted by id.
+    /// Returns the requested items and the number of items before slicing
+    fn apply<'a, T: ListItem + 'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a T>,
+    ) -> (Vec<&'a T>, usize) {
+        let mut items: Vec<&T> = items
+            .into_iter()
+            .filter(|item| {
+                self.filters.iter().all(|(field, values)| {
+                    item.field(field).map_or(true, |value| {
+                        values.iter().any(|expected| value.matches(expected))
+                    })
+                })
+            })
+            .collect();
+
+        if let Some(sort) = &self.sort {
+            // the sort is stable, so equal items are kept in the order of ids
+            items.sort_by(|a, b| {
+                let ordering = compare_fields(a.field(sort), b.field(sort));
+
+                if self.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        let total = items.len();
+
+        let (start, end) = match self.page {
+            Some(page) => {
+                let limit = self.page_limit();
+
+                ((page - 1).saturating_mul(limit), page.saturating_mul(limit))
+            }
+            None => {
+                let start = self.start.unwrap_or(0);
+                let end = self
+                    .end
+                    .or(self.limit.map(|limit| start.saturating_add(limit)))
+                    .unwrap_or(total);
+
+                (start, end)
+            }
+        };
+
+        let end = end.min(total);
+        let start = start.min(end);
+
+        items.truncate(end);
+        items.drain(..start);
+
+        (items, total)
+    }
+
+    fn page_limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).max(1)
+    }
+
+    /// Ids of batched requests like `/users?id=1&id=2` without other parameters
+    fn ids(&self) -> Option<Vec<i64>> {
+        let [(field, values)] = self.filters.as_slice() else {
+            return None;
+        };
+
+        let sliced = self.start.is_some()
+            || self.end.is_some()
+            || self.limit.is_some()
+            || self.page.is_some();
+
+        if field != "id" || self.sort.is_some() || sliced {
+            return None;
+        }
+
+        values.iter().map(|value| value.parse().ok()).collect()
+    }
+
+    /// Responds to batched requests by id without going through the whole table.
+    /// The found items are returned in the requested order, repeated ids included
+    pub fn respond_by_ids<T: Serialize>(&self, get: impl Fn(i64) -> Option<T>) -> Option<Response> {
+        let items: Vec<T> = self.ids()?.into_iter().filter_map(get).collect();
+
+        Some(([(TOTAL_COUNT, HeaderValue::from(items.len()))], Json(items)).into_response())
+    }
+
+    /// Builds the response with the `X-Total-Count` header
+    /// and the `Link` header if `_page` is set.
+    /// Only the requested items are serialized
+    pub fn respond<'a, T: ListItem + 'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a T>,
+        uri: &Uri,
+        headers: &HeaderMap,
+    ) -> Result<Response, AppError> {
+        let (items, total) = self.apply(items);
+
+        let mut response_headers = HeaderMap::new();
+
+        response_headers.insert(TOTAL_COUNT, HeaderValue::from(total));
+
+        if let Some(page) = self.page {
+            let link = page_links(uri, headers, page, total.div_ceil(self.page_limit()));
+
+            if let Ok(link) = HeaderValue::from_str(&link) {
+                response_headers.insert(header::LINK, link);
+            }
+        }
+
+        Ok((response_headers, Json(items)).into_response())
+    }
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize, AppError> {
+    value
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("Invalid `{key}` {value}, expected a number")))
+}
+
+/// Value of a field of a listed item, used for the filters and the sort
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field<'a> {
+    Int(i64),
+    Float(f64),
+    Str(&'a str),
+    Bool(bool),
+}
+
+impl Field<'_> {
+    fn matches(&self, expected: &str) -> bool {
+        match self {
+            Field::Int(value) => expected.parse() == Ok(*value),
+            Field::Float(value) => expected.parse() == Ok(*value),
+            Field::Str(value) => *value == expected,
+            Field::Bool(value) => expected.parse() == Ok(*value),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Field::Int(value) => Some(*value as f64),
+            Field::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Item of a list route that can be filtered and sorted by its fields
+/// without converting it to json
+pub trait ListItem: Serialize {
+    /// Value of the field by its name in the response,
+    /// nested fields are separated with dots like `address.zipcode`
+    fn field(&self, name: &str) -> Option<Field<'_>>;
+}
+
+/// Numbers are compared by value and strings lexicographically,
+/// items without the field go last
+fn compare_fields(a: Option<Field>, b: Option<Field>) -> Ordering {
+    match (a, b) {
+        (Some(Field::Str(a)), Some(Field::Str(b))) => a.cmp(b),
+        (Some(Field::Bool(a)), Some(Field::Bool(b))) => a.cmp(&b),
+        (Some(a), Some(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal,
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl ListItem for UserData {
+    fn field(&self, name: &str) -> Option<Field<'_>> {
+        Some(match name {
+            "id" => Field::Int(self.id),
+            "name" => Field::Str(&self.name),
+            "phone" => Field::Str(&self.phone),
+            "email" => Field::Str(&self.email),
+            "username" => Field::Str(&self.username),
+            "website" => Field::Str(&self.website),
+            "address.zipcode" => Field::Str(&self.address.zipcode),
+            "address.geo.lat" => Field::Float(self.address.geo.lat),
+            "address.geo.lng" => Field::Float(self.address.geo.lng),
+            _ => return None,
+        })
+    }
+}
+
+impl ListItem for PostData {
+    fn field(&self, name: &str) -> Option<Field<'_>> {
+        Some(match name {
+            "id" => Field::Int(self.id),
+            "title" => Field::Str(&self.title),
+            "userId" => Field::Int(self.user_id),
+            "body" => Field::Str(&self.body),
+            _ => return None,
+        })
+    }
+}
+
+impl ListItem for CommentData {
+    fn field(&self, name: &str) -> Option<Field<'_>> {
+        Some(match name {
+            "id" => Field::Int(self.id),
+            "postId" => Field::Int(self.post_id),
+            "name" => Field::Str(&self.name),
+            "email" => Field::Str(&self.email),
+            "body" => Field::Str(&self.body),
+            _ => return None,
+        })
+    }
+}
+
+impl ListItem for AlbumData {
+    fn field(&self, name: &str) -> Option<Field<'_>> {
+        Some(match name {
+            "id" => Field::Int(self.id),
+            "userId" => Field::Int(self.user_id),
+            "title" => Field::Str(&self.title),
+            _ => return None,
+        })
+    }
+}
+
+impl ListItem for PhotoData {
+    fn field(&self, name: &str) -> Option<Field<'_>> {
+        Some(match name {
+            "id" => Field::Int(self.id),
+            "albumId" => Field::Int(self.album_id),
+            "title" => Field::Str(&self.title),
+            "url" => Field::Str(&self.url),
+            "thumbnailUrl" => Field::Str(&self.thumbnail_url),
+            _ => return None,
+        })
+    }
+}
+
+impl ListItem for TodoData {
+    fn field(&self, name: &str) -> Option<Field<'_>> {
+        Some(match name {
+            "id" => Field::Int(self.id),
+            "userId" => Field::Int(self.user_id),
+            "title" => Field::Str(&self.title),
+            "completed" => Field::Bool(self.completed),
+            _ => return None,
+        })
+    }
+}
+
+/// Links to the first, previous, next and last pages
+/// in the format of the `Link` header
+fn page_links(uri: &Uri, headers: &HeaderMap, page: usize, pages: usize) -> String {
+    let last = pages.max(1);
+    let host = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("localhost");
+
+    // keep other parameters as they were sent
+    let mut query: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some("_page"))
+        .collect();
+    query.push("_page=");
+    let query = query.join("&");
+
+    let link = |page: usize, rel: &str| {
+        format!("<http://{host}{}?{query}{page}>; rel=\"{rel}\"", uri.path())
+    };
+
+    let mut links = vec![link(1, "first")];
+
+    if page > 1 {
+        links.push(link((page - 1).min(last), "prev"));
+    }
+
+    if page < last {
+        links.push(link(page + 1, "next"));
+    }
+
+    links.push(link(last, "last"));
+
+    links.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, Uri};
+
+    use super::{page_links, ListParams};
+    use crate::PostData;
+
+    fn parse(query: &str) -> ListParams {
+        let params = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+
+        ListParams::parse(params).unwrap()
+    }
+
+    fn apply(query: &str) -> (Vec<i64>, usize) {
+        let items: Vec<_> = (1..=5)
+            .map(|id| PostData {
+                id,
+                title: format!("{}", 6 - id),
+                user_id: id % 2,
+                body: String::new(),
+            })
+            .collect();
+
+        let (items, total) = parse(query).apply(&items);
+
+        (items.iter().map(|item| item.id).collect(), total)
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let (items, total) = apply("userId=1");
+
+        assert_eq!(items, [1, 3, 5]);
+        assert_eq!(total, 3);
+
+        assert_eq!(apply("id=4&id=2&id=9").0, [2, 4]);
+        assert_eq!(apply("_sort=title").0, [5, 4, 3, 2, 1]);
+        assert_eq!(apply("_sort=userId&_order=desc").0, [1, 3, 5, 2, 4]);
+        assert_eq!(apply("unknown=1").0, [1, 2, 3, 4, 5]);
+        assert_eq!(apply("userId=0&unknown=1").0, [2, 4]);
+        assert!(ListParams::parse(vec![("_order".to_owned(), "up".to_owned())]).is_err());
+    }
+
+    #[test]
+    fn test_ids() {
+        assert_eq!(parse("id=4&id=2&id=4").ids(), Some(vec![4, 2, 4]));
+        assert_eq!(parse("id=4&_sort=title").ids(), None);
+        assert_eq!(parse("id=4&userId=1").ids(), None);
+        assert_eq!(parse("id=one").ids(), None);
+        assert_eq!(parse("").ids(), None);
+    }
+
+    #[test]
+    fn test_slice() {
+        assert_eq!(apply("_start=1&_limit=2").0, [2, 3]);
+        assert_eq!(apply("_start=3&_end=10").0, [4, 5]);
+
+        let (items, total) = apply("_page=2&_limit=2&_sort=id&_order=desc");
+
+        assert_eq!(items, [3, 2]);
+        assert_eq!(total, 5);
+        assert!(apply("_page=4&_limit=2").0.is_empty());
+    }
+
+    #[test]
+    fn test_page_links() {
+        let uri: Uri = "/posts?userId=1&_page=2&_limit=2".parse().unwrap();
+
+        assert_eq!(
+            page_links(&uri, &HeaderMap::new(), 2, 3),
+            "<http://localhost/posts?userId=1&_limit=2&_page=1>; rel=\"first\", \
+             <http://localhost/posts?userId=1&_limit=2&_page=1>; rel=\"prev\", \
+             <http://localhost/posts?userId=1&_limit=2&_page=3>; rel=\"next\", \
+             <http://localhost/posts?userId=1&_limit=2&_page=3>; rel=\"last\""
+        );
+    }
+}
@@ -1,18 +1,13 @@
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
 use axum::{
-    extract::Request,
     http::StatusCode,
-    middleware::Next,
     response::IntoResponse,
     routing::{get, post},
     Router,
 };
-use mock_api::{utils::env_default, AppState};
+use mock_api::{config::Config, utils::env_default, AppState};
 use tokio::net::TcpListener;
-use tower_governor::{
-    governor::GovernorConfigBuilder, key_extractor::GlobalKeyExtractor, GovernorLayer,
-};
 use tracing_subscriber::prelude::*;
 
 #[tokio::main]
@@ -23,60 +18,113 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // The delay is expressed in milliseconds
-    // Used to add a delay for each request
-    // with the aim to "simulate" real world
-    let delay = env_default("MOCK_SERVER_DELAY", 5);
-    let delay = Duration::from_millis(delay);
+    // Delays with the aim to "simulate" real world, rate limit,
+    // faults of an unreliable upstream and the size of the data.
+    // Can be changed at runtime with `/admin`
+    let config = Config::from_env().unwrap_or_else(|e| panic!("Invalid config: {e:#}"));
 
-    // Number of requests the server can handle in a given moment
-    // after that number the server triggers rate-limiting
-    let burst_size = env_default("MOCK_SERVER_BURST_SIZE", 1000);
+    // Seed of the initial data, the same seed generates the same data.
+    // Random if not specified
+    let seed = env_default("MOCK_SERVER_SEED", rand::random::<u32>().into());
 
-    let rate_limiter_config = Arc::new(
-        GovernorConfigBuilder::default()
-            .per_nanosecond((1000000000 / burst_size).into())
-            .burst_size(burst_size)
-            .key_extractor(GlobalKeyExtractor)
-            .finish()
-            .unwrap(),
-    );
+    println!("generating data with seed {seed}, size {:?}", config.size);
 
     // Shared state of the API, used to keep the data that will be served
-    let state = Arc::new(AppState::default());
+    let state = Arc::new(AppState::new(seed, config));
+
+    // Endpoints with the data, requests to them are counted
+    // to see how many upstream calls the GraphQL server makes.
+    // Delays, rate limit and faults apply only to them,
+    // so the control endpoints below always respond right away
+    let data_router = Router::new()
+        .route(
+            "/posts",
+            get(mock_api::routes::get_posts::handle).post(mock_api::routes::create_post::handle),
+        )
+        .route(
+            "/posts/:post_id",
+            get(mock_api::routes::get_post::handle)
+                .put(mock_api::routes::update_post::handle)
+                .patch(mock_api::routes::patch_post::handle)
+                .delete(mock_api::routes::delete_post::handle),
+        )
+        .route(
+            "/users",
+            get(mock_api::routes::get_users::handle).post(mock_api::routes::create_user::handle),
+        )
+        .route(
+            "/users/:user_id",
+            get(mock_api::routes::get_user::handle)
+                .put(mock_api::routes::update_user::handle)
+                .patch(mock_api::routes::patch_user::handle)
+                .delete(mock_api::routes::delete_user::handle),
+        )
+        .route("/comments", get(mock_api::routes::get_comments::handle))
+        .route(
+            "/comments/:comment_id",
+            get(mock_api::routes::get_comment::handle),
+        )
+        .route("/albums", get(mock_api::routes::get_albums::handle))
+        .route(
+            "/albums/:album_id",
+            get(mock_api::routes::get_album::handle),
+        )
+        .route("/photos", get(mock_api::routes::get_photos::handle))
+        .route(
+            "/photos/:photo_id",
+            get(mock_api::routes::get_photo::handle),
+        )
+        .route("/todos", get(mock_api::routes::get_todos::handle))
+        .route("/todos/:todo_id", get(mock_api::routes::get_todo::handle))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            mock_api::faults::inject_faults,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            mock_api::stats::count_requests,
+        ))
+        // This middleware is responsible to apply the delay functionality
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            mock_api::delay::delay,
+        ))
+        // Rejects the requests if rate limiting is enabled
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            mock_api::rate_limit::limit_rate,
+        ));
 
     // The router and the available endpoints
-    let mut router = Router::new()
+    let router = Router::new()
         .route(
             "/",
             get(|| async { (StatusCode::OK, "BENCHING").into_response() }),
         )
-        .route("/posts", get(mock_api::routes::get_posts::handle))
-        .route("/posts/:post_id", get(mock_api::routes::get_post::handle))
-        .route("/users", get(mock_api::routes::get_users::handle))
-        .route("/users/:user_id", get(mock_api::routes::get_user::handle))
+        .merge(data_router)
         .route("/reset", post(mock_api::routes::reset_database::handle))
-        .layer(axum::middleware::from_fn(
-            // This middleware is responsible to apply the delay functionality
-            move |request: Request, next: Next| {
-                let delay = delay.clone();
-                async move {
-                    let response = next.run(request).await;
-                    tokio::time::sleep(delay).await;
-                    response
-                }
-            },
-        ))
+        .route(
+            "/database",
+            get(mock_api::routes::get_database::handle)
+                .put(mock_api::routes::put_database::handle),
+        )
+        .route(
+            "/stats",
+            get(mock_api::routes::get_stats::handle)
+                .delete(mock_api::routes::reset_stats::handle),
+        )
+        .route(
+            "/admin",
+            get(mock_api::routes::get_admin::handle)
+                .patch(mock_api::routes::update_admin::handle),
+        )
         .with_state(state);
 
-    // Check if rate limiting is enabled and apply it
-    if env_default("MOCK_SERVER_LIMITER_ENABLED", false) {
-        router = router.layer(GovernorLayer {
-            config: rate_limiter_config,
-        })
-    }
+    // Port to listen on, the runner picks a free one
+    // to run several projects at the same time
+    let port: u16 = env_default("MOCK_SERVER_PORT", 3000);
 
-    let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+    let listener = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
 
     println!("listening on http://{}", listener.local_addr().unwrap());
 
@@ -0,0 +1,133 @@
+use std::{num::NonZeroU32, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{
+    clock::{Clock, DefaultClock},
+    DefaultDirectRateLimiter, Quota,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::env_default, AppState};
+
+/// Limit of the requests to all routes together
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct RateLimit {
+    pub enabled: bool,
+    /// Number of requests the server can handle in a given moment,
+    /// the same number of requests is replenished every second
+    pub burst_size: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            burst_size: 1000,
+        }
+    }
+}
+
+impl RateLimit {
+    /// Reads the limit from `MOCK_SERVER_LIMITER_ENABLED` and `MOCK_SERVER_BURST_SIZE`
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            enabled: env_default("MOCK_SERVER_LIMITER_ENABLED", default.enabled),
+            burst_size: env_default("MOCK_SERVER_BURST_SIZE", default.burst_size),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.burst_size == 0 {
+            anyhow::bail!("`burstSize` should be positive");
+        }
+
+        Ok(())
+    }
+}
+
+/// Helper struct that keeps the state of the limit
+pub struct RateLimiter {
+    limit: RateLimit,
+    limiter: Option<DefaultDirectRateLimiter>,
+}
+
+impl RateLimiter {
+    /// The limit has to be validated
+    pub fn new(limit: RateLimit) -> Self {
+        let limiter = NonZeroU32::new(limit.burst_size)
+            .filter(|_| limit.enabled)
+            .map(|burst_size| {
+                let period =
+                    Duration::from_nanos((1_000_000_000 / u64::from(burst_size.get())).max(1));
+
+                DefaultDirectRateLimiter::direct(
+                    Quota::with_period(period).unwrap().allow_burst(burst_size),
+                )
+            });
+
+        Self { limit, limiter }
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Time to wait until the next request is allowed, `None` if it's allowed now
+    fn check(&self) -> Option<Duration> {
+        let limiter = self.limiter.as_ref()?;
+
+        limiter
+            .check()
+            .err()
+            .map(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+    }
+}
+
+/// Middleware that rejects the requests above the rate limit
+pub async fn limit_rate(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let wait = state.rate_limiter.read().unwrap().check();
+
+    match wait {
+        Some(wait) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, wait.as_secs().to_string())],
+            format!("Too Many Requests! Wait for {}s", wait.as_secs()),
+        )
+            .into_response(),
+        None => next.run(request).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimit, RateLimiter};
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(RateLimit {
+            enabled: true,
+            burst_size: 3,
+        });
+
+        assert_eq!((0..5).filter(|_| limiter.check().is_none()).count(), 3);
+
+        let disabled = RateLimiter::new(RateLimit {
+            enabled: false,
+            burst_size: 3,
+        });
+
+        assert!((0..5).all(|_| disabled.check().is_none()));
+    }
+}
@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+
+use crate::{AppError, AppState, NewPostData};
+
+/// route handler for creating a post
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    Json(post): Json<NewPostData>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::CREATED, Json(state.db.create_post(post))))
+}
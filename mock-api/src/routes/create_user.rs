@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+
+use crate::{AppError, AppState, NewUserData};
+
+/// route handler for creating a user
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    Json(user): Json<NewUserData>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok((StatusCode::CREATED, Json(state.db.create_user(user))))
+}
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{AppError, AppState};
+
+/// route handler for deleting a post
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    post_id: Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let post_id = post_id.0;
+    match state.db.delete_post(post_id) {
+        Some(post) => Ok(Json(post).into_response()),
+        None => Err(AppError::NotFound(format!(
+            "Post with id {} not found",
+            post_id
+        ))),
+    }
+}
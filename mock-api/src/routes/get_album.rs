@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{AppError, AppState};
+
+/// route handler for getting an album
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    album_id: Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let album_id = album_id.0;
+    match state.db.album(album_id) {
+        Some(album) => Ok(Json(album).into_response()),
+        None => Err(AppError::NotFound(format!(
+            "Album with id {} not found",
+            album_id
+        ))),
+    }
+}
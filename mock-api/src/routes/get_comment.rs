@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{AppError, AppState};
+
+/// route handler for getting a comment
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    comment_id: Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let comment_id = comment_id.0;
+    match state.db.comment(comment_id) {
+        Some(comment) => Ok(Json(comment).into_response()),
+        None => Err(AppError::NotFound(format!(
+            "Comment with id {} not found",
+            comment_id
+        ))),
+    }
+}
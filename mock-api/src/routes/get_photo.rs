@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{AppError, AppState};
+
+/// route handler for getting a photo
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    photo_id: Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let photo_id = photo_id.0;
+    match state.db.photo(photo_id) {
+        Some(photo) => Ok(Json(photo).into_response()),
+        None => Err(AppError::NotFound(format!(
+            "Photo with id {} not found",
+            photo_id
+        ))),
+    }
+}
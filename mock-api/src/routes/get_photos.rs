@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, Uri},
+    response::IntoResponse,
+};
+
+use crate::{list::ListParams, AppError, AppState};
+
+/// route handler for getting all photos,
+/// supports filters, sorting and pagination
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<impl IntoResponse, AppError> {
+    let params = ListParams::parse(params)?;
+
+    if let Some(response) = params.respond_by_ids(|id| state.db.photo(id)) {
+        return Ok(response);
+    }
+
+    params.respond(state.db.read().photos.values(), &uri, &headers)
+}
@@ -1,10 +1,26 @@
 use std::sync::Arc;
 
-use axum::{extract::State, response::IntoResponse, Json};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, Uri},
+    response::IntoResponse,
+};
 
-use crate::{AppError, AppState};
+use crate::{list::ListParams, AppError, AppState};
 
-/// route handler for getting all posts
-pub async fn handle(state: State<Arc<AppState>>) -> Result<impl IntoResponse, AppError> {
-    Ok(Json(state.db.posts()))
+/// route handler for getting all posts,
+/// supports filters, sorting and pagination
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<impl IntoResponse, AppError> {
+    let params = ListParams::parse(params)?;
+
+    if let Some(response) = params.respond_by_ids(|id| state.db.post(id)) {
+        return Ok(response);
+    }
+
+    params.respond(state.db.read().posts.values(), &uri, &headers)
 }
@@ -0,0 +1,10 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::{AppError, AppState};
+
+/// route handler for getting the request counters
+pub async fn handle(state: State<Arc<AppState>>) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(state.stats.snapshot()))
+}
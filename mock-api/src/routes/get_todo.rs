@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{AppError, AppState};
+
+/// route handler for getting a todo
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    todo_id: Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let todo_id = todo_id.0;
+    match state.db.todo(todo_id) {
+        Some(todo) => Ok(Json(todo).into_response()),
+        None => Err(AppError::NotFound(format!(
+            "Todo with id {} not found",
+            todo_id
+        ))),
+    }
+}
@@ -2,26 +2,26 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Query, State},
+    http::{HeaderMap, Uri},
     response::IntoResponse,
-    Json,
 };
 
-use crate::{AppError, AppState};
+use crate::{list::ListParams, AppError, AppState};
 
-/// route handler for getting all users
+/// route handler for getting all users, specific users are
+/// requested with repeated `id` params and returned in the requested order.
+/// Supports filters, sorting and pagination
 pub async fn handle(
     state: State<Arc<AppState>>,
-    Query(params): Query<Vec<(String, i64)>>,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(params): Query<Vec<(String, String)>>,
 ) -> Result<impl IntoResponse, AppError> {
-    let users = if params.is_empty() {
-        state.db.users()
-    } else {
-        params
-            .into_iter()
-            .filter(|(key, _)| key == "id")
-            .filter_map(|(_, id)| state.db.user(id))
-            .collect()
-    };
+    let params = ListParams::parse(params)?;
 
-    Ok(Json(users))
+    if let Some(response) = params.respond_by_ids(|id| state.db.user(id)) {
+        return Ok(response);
+    }
+
+    params.respond(state.db.read().users.values(), &uri, &headers)
 }
@@ -1,5 +1,27 @@
+pub mod create_post;
+pub mod create_user;
+pub mod delete_post;
+pub mod delete_user;
+pub mod get_admin;
+pub mod get_album;
+pub mod get_albums;
+pub mod get_comment;
+pub mod get_comments;
+pub mod get_database;
+pub mod get_photo;
+pub mod get_photos;
 pub mod get_post;
 pub mod get_posts;
+pub mod get_stats;
+pub mod get_todo;
+pub mod get_todos;
 pub mod get_user;
 pub mod get_users;
+pub mod patch_post;
+pub mod patch_user;
+pub mod put_database;
 pub mod reset_database;
+pub mod reset_stats;
+pub mod update_admin;
+pub mod update_post;
+pub mod update_user;
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::{Map, Value};
+
+use crate::{AppError, AppState};
+
+/// route handler for changing some fields of a post
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    post_id: Path<i64>,
+    Json(patch): Json<Map<String, Value>>,
+) -> Result<impl IntoResponse, AppError> {
+    let post_id = post_id.0;
+    match state.db.patch_post(post_id, patch) {
+        Ok(Some(post)) => Ok(Json(post).into_response()),
+        Ok(None) => Err(AppError::NotFound(format!(
+            "Post with id {} not found",
+            post_id
+        ))),
+        Err(e) => Err(AppError::BadRequest(format!("Invalid post: {e}"))),
+    }
+}
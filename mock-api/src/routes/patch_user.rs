@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::{Map, Value};
+
+use crate::{AppError, AppState};
+
+/// route handler for changing some fields of a user
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    user_id: Path<i64>,
+    Json(patch): Json<Map<String, Value>>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id.0;
+    match state.db.patch_user(user_id, patch) {
+        Ok(Some(user)) => Ok(Json(user).into_response()),
+        Ok(None) => Err(AppError::NotFound(format!(
+            "User with id {} not found",
+            user_id
+        ))),
+        Err(e) => Err(AppError::BadRequest(format!("Invalid user: {e}"))),
+    }
+}
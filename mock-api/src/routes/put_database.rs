@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{database::Dataset, AppError, AppState};
+
+/// route handler for replacing the whole database
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    Json(dataset): Json<Dataset>,
+) -> Result<impl IntoResponse, AppError> {
+    match state.db.load(dataset) {
+        Ok(()) => Ok(Json(json!({"status": "Database loaded successfully"}))),
+        Err(_e) => Err(AppError::InternalServerError(
+            "Failed to load database".to_string(),
+        )),
+    }
+}
@@ -1,14 +1,47 @@
 use std::sync::Arc;
 
-use axum::{extract::State, response::IntoResponse, Json};
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
 use serde_json::json;
 
-use crate::{AppError, AppState};
+use crate::{database::DatasetSize, AppError, AppState};
+
+/// Optional body of the reset request
+#[derive(Deserialize, Default)]
+pub struct ResetParams {
+    /// Seed of the generated data, random if not specified
+    seed: Option<u64>,
+    /// Number of entities of every kind, the size of the current data if not specified
+    size: Option<DatasetSize>,
+}
 
 /// route handler for resetting the database
-pub async fn handle(state: State<Arc<AppState>>) -> Result<impl IntoResponse, AppError> {
-    match state.db.reset() {
-        Ok(()) => Ok(Json(json!({"status": "Database reset successfully"}))),
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    params: Result<Json<ResetParams>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let params = match params {
+        Ok(Json(params)) => params,
+        // the body is optional
+        Err(JsonRejection::MissingJsonContentType(_)) => ResetParams::default(),
+        // don't silently ignore typos like `{"size": {"post": 2000}}`
+        Err(e) => return Err(AppError::BadRequest(e.body_text())),
+    };
+    let seed = params.seed.unwrap_or_else(|| rand::random::<u32>().into());
+    let size = params.size.unwrap_or_else(|| state.db.size());
+
+    if let Err(e) = size.validate() {
+        return Err(AppError::BadRequest(format!("Invalid size: {e}")));
+    }
+
+    match state.db.reset(seed, size) {
+        Ok(()) => Ok(Json(
+            json!({"status": "Database reset successfully", "seed": seed, "size": size}),
+        )),
         Err(_e) => Err(AppError::InternalServerError(
             "Failed to reset database".to_string(),
         )),
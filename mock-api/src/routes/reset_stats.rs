@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{AppError, AppState};
+
+/// route handler for resetting the request counters
+pub async fn handle(state: State<Arc<AppState>>) -> Result<impl IntoResponse, AppError> {
+    state.stats.reset();
+
+    Ok(Json(json!({"status": "Stats reset successfully"})))
+}
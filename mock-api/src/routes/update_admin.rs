@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::{config::ConfigUpdate, AppError, AppState};
+
+/// route handler for changing the config, returns the changed config
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    Json(update): Json<ConfigUpdate>,
+) -> Result<impl IntoResponse, AppError> {
+    if let Err(e) = state.update_config(update) {
+        return Err(AppError::BadRequest(format!("{e:#}")));
+    }
+
+    Ok(Json(state.config()))
+}
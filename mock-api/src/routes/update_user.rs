@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{AppError, AppState, NewUserData};
+
+/// route handler for replacing a user
+pub async fn handle(
+    state: State<Arc<AppState>>,
+    user_id: Path<i64>,
+    Json(user): Json<NewUserData>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id.0;
+    match state.db.update_user(user_id, user) {
+        Some(user) => Ok(Json(user).into_response()),
+        None => Err(AppError::NotFound(format!(
+            "User with id {} not found",
+            user_id
+        ))),
+    }
+}
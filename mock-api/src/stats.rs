@@ -0,0 +1,105 @@
+use std::{collections::BTreeMap, sync::Arc, sync::Mutex};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+
+use crate::AppState;
+
+/// Number of requests to a single route
+#[derive(Serialize, Default, Clone, Debug)]
+pub struct RouteStats {
+    pub requests: u64,
+    /// Number of `id` query parameters, i.e. entities requested in batches
+    pub batched_ids: u64,
+}
+
+/// Counters of the requests received by the data routes
+#[derive(Serialize, Default, Debug)]
+pub struct StatsSnapshot {
+    pub total: u64,
+    /// Counters by method and route, e.g. `GET /users/:user_id`
+    pub routes: BTreeMap<String, RouteStats>,
+}
+
+/// Helper struct that is used to count the requests
+/// to see how many upstream calls the GraphQL server makes
+#[derive(Default)]
+pub struct RequestStats {
+    routes: Mutex<BTreeMap<String, RouteStats>>,
+}
+
+impl RequestStats {
+    /// Used to count a request to the route
+    pub fn record(&self, route: String, batched_ids: u64) {
+        let mut routes = self.routes.lock().unwrap();
+        let stats = routes.entry(route).or_default();
+
+        stats.requests += 1;
+        stats.batched_ids += batched_ids;
+    }
+
+    /// Used to get the current counters
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let routes = self.routes.lock().unwrap().clone();
+        let total = routes.values().map(|stats| stats.requests).sum();
+
+        StatsSnapshot { total, routes }
+    }
+
+    /// Used to set all counters to zero
+    pub fn reset(&self) {
+        self.routes.lock().unwrap().clear();
+    }
+}
+
+/// Middleware that counts the requests by route
+pub async fn count_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(path) = request.extensions().get::<MatchedPath>() {
+        let batched_ids = request
+            .uri()
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| pair.split('=').next() == Some("id"))
+            .count();
+
+        state.stats.record(
+            format!("{} {}", request.method(), path.as_str()),
+            batched_ids as u64,
+        );
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RequestStats;
+
+    #[test]
+    fn test_request_stats() {
+        let stats = RequestStats::default();
+
+        stats.record("GET /posts".to_owned(), 0);
+        stats.record("GET /users".to_owned(), 3);
+        stats.record("GET /users".to_owned(), 2);
+
+        let snapshot = stats.snapshot();
+
+        assert_eq!(snapshot.total, 3);
+        assert_eq!(snapshot.routes["GET /users"].requests, 2);
+        assert_eq!(snapshot.routes["GET /users"].batched_ids, 5);
+
+        stats.reset();
+
+        assert_eq!(stats.snapshot().total, 0);
+    }
+}
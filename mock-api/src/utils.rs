@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use std::{path::Path, str::FromStr};
+
+use anyhow::Context;
+use serde::de::DeserializeOwned;
 
 /// Used to read variables from Environment
 pub fn env_default<T: FromStr>(name: &str, default_value: T) -> T {
@@ -7,3 +10,12 @@ pub fn env_default<T: FromStr>(name: &str, default_value: T) -> T {
         .and_then(|v| v.parse().ok())
         .unwrap_or(default_value)
 }
+
+/// Used to read config files
+pub fn read_json_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> anyhow::Result<T> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
@@ -9,6 +9,6 @@ const server = new ApolloServer({
 });
 
 // Start the server
-server.listen({ port: 8000 }).then(({ url }) => {
+server.listen({ port: process.env.SERVER_PORT || 8000 }).then(({ url }) => {
   console.log(`🚀 Server ready at ${url}`);
 });
@@ -1,6 +1,6 @@
 const axios = require('axios');
 
-const BASE_URL = 'http://localhost:3000'; // This is your upstream REST API
+const BASE_URL = `http://localhost:${process.env.MOCK_SERVER_PORT || 3000}`; // This is your upstream REST API
 
 const resolvers = {
   Query: {
@@ -26,11 +26,161 @@ const resolvers = {
     user: async (_, { id }) => {
       const response = await axios.get(`${BASE_URL}/users/${id}`);
       return response.data;
+    },
+
+    // Resolver for fetching all comments
+    comments: async () => {
+      const response = await axios.get(`${BASE_URL}/comments`);
+      return response.data;
+    },
+
+    // Resolver for fetching a single comment by ID
+    comment: async (_, { id }) => {
+      const response = await axios.get(`${BASE_URL}/comments/${id}`);
+      return response.data;
+    },
+
+    // Resolver for fetching all albums
+    albums: async () => {
+      const response = await axios.get(`${BASE_URL}/albums`);
+      return response.data;
+    },
+
+    // Resolver for fetching a single album by ID
+    album: async (_, { id }) => {
+      const response = await axios.get(`${BASE_URL}/albums/${id}`);
+      return response.data;
+    },
+
+    // Resolver for fetching all photos
+    photos: async () => {
+      const response = await axios.get(`${BASE_URL}/photos`);
+      return response.data;
+    },
+
+    // Resolver for fetching a single photo by ID
+    photo: async (_, { id }) => {
+      const response = await axios.get(`${BASE_URL}/photos/${id}`);
+      return response.data;
+    },
+
+    // Resolver for fetching all todos
+    todos: async () => {
+      const response = await axios.get(`${BASE_URL}/todos`);
+      return response.data;
+    },
+
+    // Resolver for fetching a single todo by ID
+    todo: async (_, { id }) => {
+      const response = await axios.get(`${BASE_URL}/todos/${id}`);
+      return response.data;
+    }
+  },
+
+  Mutation: {
+    // Resolver for creating a post
+    createPost: async (_, { input }) => {
+      const response = await axios.post(`${BASE_URL}/posts`, input);
+      return response.data;
+    },
+
+    // Resolver for changing fields of a post
+    updatePost: async (_, { id, input }) => {
+      const response = await axios.patch(`${BASE_URL}/posts/${id}`, input);
+      return response.data;
+    },
+
+    // Resolver for deleting a post
+    deletePost: async (_, { id }) => {
+      const response = await axios.delete(`${BASE_URL}/posts/${id}`);
+      return response.data;
+    },
+
+    // Resolver for creating a user
+    createUser: async (_, { input }) => {
+      const response = await axios.post(`${BASE_URL}/users`, input);
+      return response.data;
+    },
+
+    // Resolver for changing fields of a user
+    updateUser: async (_, { id, input }) => {
+      const response = await axios.patch(`${BASE_URL}/users/${id}`, input);
+      return response.data;
+    },
+
+    // Resolver for deleting a user
+    deleteUser: async (_, { id }) => {
+      const response = await axios.delete(`${BASE_URL}/users/${id}`);
+      return response.data;
     }
   },
 
   Post: {
     // Resolver for fetching the user of a post
+    user: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/users/${parent.userId}`);
+      return response.data;
+    },
+
+    // Resolver for fetching the comments of a post
+    comments: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/comments`, { params: { postId: parent.id } });
+      return response.data;
+    }
+  },
+
+  User: {
+    // Resolver for fetching the posts of a user
+    posts: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/posts`, { params: { userId: parent.id } });
+      return response.data;
+    },
+
+    // Resolver for fetching the albums of a user
+    albums: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/albums`, { params: { userId: parent.id } });
+      return response.data;
+    },
+
+    // Resolver for fetching the todos of a user
+    todos: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/todos`, { params: { userId: parent.id } });
+      return response.data;
+    }
+  },
+
+  Comment: {
+    // Resolver for fetching the post of a comment
+    post: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/posts/${parent.postId}`);
+      return response.data;
+    }
+  },
+
+  Album: {
+    // Resolver for fetching the user of an album
+    user: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/users/${parent.userId}`);
+      return response.data;
+    },
+
+    // Resolver for fetching the photos of an album
+    photos: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/photos`, { params: { albumId: parent.id } });
+      return response.data;
+    }
+  },
+
+  Photo: {
+    // Resolver for fetching the album of a photo
+    album: async (parent) => {
+      const response = await axios.get(`${BASE_URL}/albums/${parent.albumId}`);
+      return response.data;
+    }
+  },
+
+  Todo: {
+    // Resolver for fetching the user of a todo
     user: async (parent) => {
       const response = await axios.get(`${BASE_URL}/users/${parent.userId}`);
       return response.data;
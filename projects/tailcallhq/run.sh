@@ -7,10 +7,15 @@ npm install -g @tailcallhq/tailcall
 # Get the directory of the script
 SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
 
-# Set the path to the schema file relative to the script location
-SCHEMA_FILE="${SCRIPT_DIR}/tailcall.graphql"
+# Set the path to the schema file with the ports provided by the runner,
+# it is kept in the results directory and replaced on every run
+SCHEMA_FILE="${RESULTS_DIR:-/tmp}/tailcall.graphql"
+
+sed -e "s/port: 8000/port: ${SERVER_PORT:-8000}/" \
+  -e "s|http://localhost:3000|http://localhost:${MOCK_SERVER_PORT:-3000}|" \
+  "${SCRIPT_DIR}/tailcall.graphql" > "${SCHEMA_FILE}"
 
 echo "Starting the server"
 
 # Start Tailcall
-TAILCALL_LOG_LEVEL=error TC_TRACKER=false tailcall start "${SCHEMA_FILE}"
+TAILCALL_LOG_LEVEL=error TC_TRACKER=false exec tailcall start "${SCHEMA_FILE}"
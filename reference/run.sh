@@ -2,4 +2,12 @@
 
 set -e
 
-TAILCALL_LOG_LEVEL=error TC_TRACKER=false tailcall start schema.graphql
+# Use the ports provided by the runner, the patched schema is kept
+# in the results directory of the project and replaced on every run
+SCHEMA_FILE="${RESULTS_DIR:-/tmp}/reference.graphql"
+
+sed -e "s/port: 8089/port: ${REFERENCE_SERVER_PORT:-8089}/" \
+  -e "s|http://localhost:3000|http://localhost:${MOCK_SERVER_PORT:-3000}|" \
+  schema.graphql > "${SCHEMA_FILE}"
+
+TAILCALL_LOG_LEVEL=error TC_TRACKER=false exec tailcall start "${SCHEMA_FILE}"
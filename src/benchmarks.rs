@@ -1,50 +1,198 @@
 use std::{
     collections::BTreeMap,
+    fmt::{self, Display},
     ops::{Deref, DerefMut},
-    path::{Path, PathBuf},
-    sync::LazyLock,
+    path::Path,
+    time::Duration,
 };
 
 use anyhow::{anyhow, bail, Context, Result};
-use regex::Regex;
+use hdrhistogram::Histogram;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use tokio::{fs, io::AsyncWriteExt};
-use tracing::{info, instrument};
-
-use crate::{command::Command, ROOT_DIR};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    command::CommandInstance,
+    load_generator::{new_histogram, run_load, LoadConfig, LoadResult},
+    request::{GraphqlClient, RestClient},
+    resources::{ResourceSampler, ResourceUsage},
+    scenarios::SCENARIOS,
+    statistics::Spread,
+    utils::env_default,
+    ROOT_DIR,
+};
 
 #[derive(Serialize, Deserialize, Default)]
-struct Stats {
+#[serde(default)]
+pub(crate) struct Stats {
     connect_errors: u64,
     read_errors: u64,
     write_errors: u64,
     timeout_errors: u64,
-    rps: u64,
+    status_errors: u64,
+    other_errors: u64,
+    pub(crate) rps: u64,
+    pub(crate) latency: Latency,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resources: Option<ResourceUsage>,
+    /// Rps of every repeated run, `rps` is the median of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rps_spread: Option<Spread>,
+    /// Set when the rps of repeated runs vary too much to be trusted
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    unreliable: bool,
+    /// Requests to the mock api made by the server per GraphQL request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream_requests_per_request: Option<f64>,
+    /// Number of entities of every kind in the mock data, see `GET /admin`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dataset: Option<DatasetSize>,
+}
+
+impl Stats {
+    /// Combines repeated runs of the same benchmark.
+    /// Errors are summed, latency is taken from all runs,
+    /// upstream requests are averaged
+    /// and resources are taken from the run closest to the median rps
+    fn aggregate(runs: Vec<Stats>, latency: &Histogram<u64>) -> Self {
+        let spread = Spread::new(runs.iter().map(|run| run.rps).collect());
+        let median = spread.median;
+
+        let upstream: Vec<f64> = runs
+            .iter()
+            .filter_map(|run| run.upstream_requests_per_request)
+            .collect();
+
+        let mut stats = Stats {
+            rps: median,
+            latency: Latency::from(latency),
+            upstream_requests_per_request: (!upstream.is_empty())
+                .then(|| upstream.iter().sum::<f64>() / upstream.len() as f64),
+            ..Default::default()
+        };
+
+        for run in &runs {
+            stats.connect_errors += run.connect_errors;
+            stats.read_errors += run.read_errors;
+            stats.write_errors += run.write_errors;
+            stats.timeout_errors += run.timeout_errors;
+            stats.status_errors += run.status_errors;
+            stats.other_errors += run.other_errors;
+        }
+
+        stats.resources = runs
+            .into_iter()
+            .min_by_key(|run| run.rps.abs_diff(median))
+            .and_then(|run| run.resources);
+        stats.rps_spread = Some(spread);
+
+        stats
+    }
 }
 
-static BENCHES: LazyLock<Result<Vec<String>>> = LazyLock::new(|| {
-    let tests_path = format!("{ROOT_DIR}/benches");
-    let mut tests = Vec::new();
+/// Latency percentiles in microseconds
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub(crate) struct Latency {
+    pub(crate) p50: u64,
+    pub(crate) p90: u64,
+    pub(crate) p99: u64,
+    pub(crate) p99_9: u64,
+    pub(crate) max: u64,
+}
 
-    for entry in std::fs::read_dir(tests_path)? {
-        let path = entry?.path();
+impl From<&Histogram<u64>> for Latency {
+    fn from(histogram: &Histogram<u64>) -> Self {
+        Self {
+            p50: histogram.value_at_quantile(0.5),
+            p90: histogram.value_at_quantile(0.9),
+            p99: histogram.value_at_quantile(0.99),
+            p99_9: histogram.value_at_quantile(0.999),
+            max: histogram.max(),
+        }
+    }
+}
 
-        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-            if ext == "lua" {
-                let name = path
-                    .file_stem()
-                    .map(|s| s.to_string_lossy())
-                    .context("expected the name")?;
-                tests.push(name.to_string());
+impl From<&LoadResult> for Stats {
+    fn from(result: &LoadResult) -> Self {
+        Self {
+            connect_errors: result.connect_errors,
+            read_errors: result.read_errors,
+            write_errors: result.write_errors,
+            timeout_errors: result.timeout_errors,
+            status_errors: result.status_errors,
+            other_errors: result.other_errors,
+            rps: result.rps(),
+            latency: Latency::from(&result.latency),
+            ..Default::default()
+        }
+    }
+}
+
+/// Request counters of the mock api, see `GET /stats`
+#[derive(Deserialize)]
+struct UpstreamStats {
+    total: u64,
+    routes: BTreeMap<String, RouteStats>,
+}
+
+#[derive(Deserialize)]
+struct RouteStats {
+    requests: u64,
+    batched_ids: u64,
+}
+
+impl UpstreamStats {
+    async fn reset(mock_api: &RestClient) -> Result<()> {
+        mock_api
+            .request(Method::DELETE, "stats")
+            .await
+            .context("Failed to reset the stats of the mock server")?;
+
+        Ok(())
+    }
+
+    async fn fetch(mock_api: &RestClient) -> Result<Self> {
+        let stats = mock_api
+            .request(Method::GET, "stats")
+            .await
+            .context("Failed to get the stats of the mock server")?;
+
+        Ok(serde_json::from_value(stats)?)
+    }
+}
+
+impl Display for UpstreamStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Upstream requests: {}", self.total)?;
+
+        for (route, stats) in &self.routes {
+            write!(f, "\n  {route}: {}", stats.requests)?;
+
+            if stats.batched_ids > 0 {
+                write!(f, " ({} batched ids)", stats.batched_ids)?;
             }
         }
+
+        Ok(())
     }
+}
+
+type DatasetSize = BTreeMap<String, u64>;
 
-    Ok(tests)
-});
+async fn fetch_dataset_size(mock_api: &RestClient) -> Result<DatasetSize> {
+    let config = mock_api
+        .request(Method::GET, "admin")
+        .await
+        .context("Failed to get the config of the mock server")?;
+
+    Ok(serde_json::from_value(config["size"].clone())?)
+}
 
 #[derive(Serialize, Deserialize, Default)]
-struct AllStats(BTreeMap<String, Stats>);
+pub(crate) struct AllStats(BTreeMap<String, Stats>);
 
 impl Deref for AllStats {
     type Target = BTreeMap<String, Stats>;
@@ -61,13 +209,40 @@ impl DerefMut for AllStats {
 }
 
 impl AllStats {
+    /// Reads `stats.json` from the results directory
+    pub(crate) async fn load(results_path: &Path) -> Result<Self> {
+        let json_path = results_path.join("stats.json");
+        let content = fs::read_to_string(&json_path)
+            .await
+            .with_context(|| format!("Failed to read `{}`", json_path.display()))?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Average rps relative to the baseline, multiplied by 1000
+    /// Checks that every benchmark of the baseline ran with the same mock data size,
+    /// stats from before the size was recorded never match
+    fn same_dataset(&self, dataset: &DatasetSize) -> bool {
+        self.values()
+            .all(|stats| stats.dataset.as_ref() == Some(dataset))
+    }
+
     fn score(&self, baseline: &AllStats) -> Result<u64> {
+        if self.is_empty() {
+            bail!("No benchmarks to score, every scenario has `bench.skip`");
+        }
+
         let mut sum: u64 = 0;
 
         for (key, stats) in &self.0 {
-            let baseline_stats = baseline
-                .get(key)
-                .context("Cannot find specific key in baseline stats")?;
+            let baseline_stats = baseline.get(key).with_context(|| {
+                format!("Benchmark `{key}` has no baseline stats, run `cargo run -- baseline` to add it or set `bench.skip`")
+            })?;
+
+            if baseline_stats.rps == 0 {
+                bail!("Baseline of benchmark `{key}` has 0 rps, run `cargo run -- baseline` to regenerate it");
+            }
+
             sum += 1000 * stats.rps / baseline_stats.rps;
         }
 
@@ -76,35 +251,150 @@ impl AllStats {
 }
 
 #[instrument(skip_all)]
-pub async fn run_benchmarks(output_path: &Path) -> Result<()> {
+pub async fn run_benchmarks(
+    output_path: &Path,
+    tested: &GraphqlClient,
+    server: &CommandInstance,
+    mock_api: &RestClient,
+) -> Result<()> {
     info!("Starting benchmark");
-    let mut mock_path = PathBuf::from(ROOT_DIR);
-    mock_path.push("benchmark.sh");
 
     fs::create_dir_all(&output_path).await?;
 
-    let baseline_stats: AllStats = serde_json::from_str(
-        &fs::read_to_string(Path::new(ROOT_DIR).join("reference/results/stats.json")).await?,
-    )?;
+    let score_path = output_path.join("score.out");
+
+    // a failed or unreliable run must not leave the score of the previous one
+    if score_path.exists() {
+        fs::remove_file(&score_path).await?;
+    }
+
+    let baseline_path = Path::new(ROOT_DIR).join("reference/results");
+
+    // the baseline run writes the baseline itself and is scored against it
+    let baseline_stats = if output_path == baseline_path {
+        None
+    } else {
+        Some(AllStats::load(&baseline_path).await?)
+    };
+
+    let dataset = fetch_dataset_size(mock_api).await?;
+
+    // rps depends on the size of the responses, so a baseline
+    // of another size can't be used for the score
+    let scored = match &baseline_stats {
+        Some(baseline_stats) if !baseline_stats.same_dataset(&dataset) => {
+            warn!("The baseline was generated with another size of the mock data or without recording it, `score.out` is not written. Run with the default `MOCK_SERVER_*` sizes or regenerate the baseline with `cargo run -- baseline`");
+
+            false
+        }
+        _ => true,
+    };
+
+    let repetitions = env_default("BENCH_REPETITIONS", 3).max(1);
+    let max_cv = env_default("BENCH_MAX_CV", 0.05);
 
     let mut stats = AllStats::default();
 
-    for bench_name in BENCHES
+    let scenarios = SCENARIOS
         .as_ref()
-        .map_err(|e| anyhow!("Failed to resolve benches due to error: {e}"))?
-    {
+        .map_err(|e| anyhow!("Failed to resolve benches due to error: {e:#}"))?;
+
+    // fail before spending time on the benchmarks that can't be scored
+    if let Some(baseline_stats) = baseline_stats.as_ref().filter(|_| scored) {
+        let missing: Vec<_> = scenarios
+            .iter()
+            .filter(|scenario| !scenario.bench.skip && !baseline_stats.contains_key(&scenario.name))
+            .map(|scenario| format!("`{}`", scenario.name))
+            .collect();
+
+        if !missing.is_empty() {
+            bail!(
+                "Benchmarks {} have no baseline stats, run `cargo run -- baseline` to add them or set `bench.skip`",
+                missing.join(", ")
+            );
+        }
+    }
+
+    for scenario in scenarios.iter().filter(|scenario| !scenario.bench.skip) {
+        let bench_name = &scenario.name;
+        let meta = &scenario.bench;
+
         info!("Run benchmark: `{bench_name}`");
 
-        let mut command = Command::from_path(&mock_path)?;
+        let config = LoadConfig {
+            url: tested.url().to_owned(),
+            body: serde_json::to_vec(scenario.request())?.into(),
+            headers: meta
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            duration: Duration::from_secs(
+                meta.duration
+                    .unwrap_or_else(|| env_default("BENCH_DURATION", 30)),
+            ),
+            connections: meta
+                .connections
+                .unwrap_or_else(|| env_default("BENCH_CONNECTIONS", 100)),
+            timeout: Duration::from_secs(env_default("BENCH_TIMEOUT", 2)),
+        };
+
+        let mut runs = Vec::with_capacity(repetitions);
+        let mut latency = new_histogram();
+        let mut report = String::new();
+
+        for run in 1..=repetitions {
+            info!("Run {run}/{repetitions} of benchmark `{bench_name}`");
+
+            UpstreamStats::reset(mock_api).await?;
+
+            let sampler = server.id().map(ResourceSampler::start);
+            let result = run_load(&config).await?;
+            let mut run_stats = Stats::from(&result);
+
+            if let Some(sampler) = sampler {
+                run_stats.resources = sampler.finish().await;
+            }
 
-        command.args(&[bench_name]);
+            let upstream = UpstreamStats::fetch(mock_api).await?;
 
-        let output = command.run_and_capture().await?;
+            if result.requests > 0 {
+                run_stats.upstream_requests_per_request =
+                    Some(upstream.total as f64 / result.requests as f64);
+            }
 
-        info!(
-            "Benchmark results:\n\n {}",
-            String::from_utf8_lossy(&output.stdout)
-        );
+            let run_report = format!(
+                "{}\n{}\n{}\n",
+                summary(&config, &result),
+                upstream,
+                run_stats
+            );
+
+            info!("Benchmark results:\n\n{run_report}");
+
+            report.push_str(&run_report);
+            latency.add(&result.latency)?;
+            runs.push(run_stats);
+        }
+
+        let mut single_stats = Stats::aggregate(runs, &latency);
+        single_stats.dataset = Some(dataset.clone());
+
+        if let Some(spread) = &single_stats.rps_spread {
+            if spread.cv > max_cv {
+                warn!(
+                    "Benchmark `{bench_name}` is unreliable: rps varies by {:.1}% between runs",
+                    spread.cv * 100.0
+                );
+                single_stats.unreliable = true;
+            }
+        }
+
+        if repetitions > 1 {
+            report.push_str(&format!(
+                "\nAggregated over {repetitions} runs:\n{single_stats}\n"
+            ));
+        }
 
         let out_path = output_path.join(format!("{bench_name}.out"));
 
@@ -115,11 +405,7 @@ pub async fn run_benchmarks(output_path: &Path) -> Result<()> {
             .open(out_path)
             .await?;
 
-        let stdout = &output.stdout;
-
-        file.write_all(stdout).await?;
-
-        let single_stats = parse_wrk(stdout).context("Failed to parse wrk output")?;
+        file.write_all(report.as_bytes()).await?;
 
         check_errors(&single_stats)
             .context("Connection errors found during execution, check benchmark output")?;
@@ -127,7 +413,7 @@ pub async fn run_benchmarks(output_path: &Path) -> Result<()> {
         stats.insert(bench_name.to_string(), single_stats);
     }
 
-    let json_path = output_path.join(format!("stats.json"));
+    let json_path = output_path.join("stats.json");
 
     let mut file = fs::OpenOptions::new()
         .write(true)
@@ -139,50 +425,145 @@ pub async fn run_benchmarks(output_path: &Path) -> Result<()> {
     file.write_all(serde_json::to_string_pretty(&stats)?.as_bytes())
         .await?;
 
-    let score = output_path.join(format!("score.out"));
+    let unreliable: Vec<_> = stats
+        .iter()
+        .filter(|(_, stats)| stats.unreliable)
+        .map(|(name, _)| format!("`{name}`"))
+        .collect();
+
+    if !unreliable.is_empty() {
+        bail!(
+            "Benchmarks {} are unreliable, the score is not published. Rerun the benchmarks on a less noisy machine",
+            unreliable.join(", ")
+        );
+    }
+
+    if !scored {
+        return Ok(());
+    }
 
     let mut file = fs::OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(score)
+        .open(score_path)
         .await?;
 
-    file.write_all(stats.score(&baseline_stats)?.to_string().as_bytes())
-        .await?;
+    let score = stats.score(baseline_stats.as_ref().unwrap_or(&stats))?;
+
+    file.write_all(score.to_string().as_bytes()).await?;
 
     Ok(())
 }
 
-fn parse_wrk(output: &Vec<u8>) -> Result<Stats> {
-    let output_str = String::from_utf8_lossy(output);
+fn summary(config: &LoadConfig, result: &LoadResult) -> String {
+    format!(
+        "Running {}s test @ {}
+  {} connections
+  {} requests in {:.2}s, {:.2}MB read",
+        config.duration.as_secs(),
+        config.url,
+        config.connections,
+        result.requests,
+        result.elapsed.as_secs_f64(),
+        result.bytes_read as f64 / (1024.0 * 1024.0),
+    )
+}
 
-    let (connect_errors, read_errors, write_errors, timeout_errors) = extract_errors(&output_str);
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Socket errors: connect {}, read {}, write {}, timeout {}",
+            self.connect_errors, self.read_errors, self.write_errors, self.timeout_errors
+        )?;
+        writeln!(f, "Non-2xx responses: {}", self.status_errors)?;
+
+        if self.other_errors > 0 {
+            writeln!(f, "Other errors: {}", self.other_errors)?;
+        }
 
-    let rps = parse_u64(&output_str, Regex::new(r"Requests\/sec:\s+(\d+).?\d+")?)
-        .context("Failed to parse rps")?;
+        writeln!(f, "Latency of responses: {}", self.latency)?;
+
+        if let Some(spread) = self.rps_spread.as_ref().filter(|s| s.samples.len() > 1) {
+            writeln!(
+                f,
+                "Requests/sec of runs: {:?}, stddev {:.1}, 95% CI [{:.1}, {:.1}], cv {:.1}%",
+                spread.samples,
+                spread.stddev,
+                spread.ci95.0,
+                spread.ci95.1,
+                spread.cv * 100.0
+            )?;
+        }
 
-    Ok(Stats {
-        connect_errors,
-        read_errors,
-        write_errors,
-        timeout_errors,
-        rps,
-    })
+        if let Some(resources) = &self.resources {
+            writeln!(
+                f,
+                "Server CPU: avg {:.1}%, peak {:.1}%",
+                resources.cpu_avg, resources.cpu_peak
+            )?;
+            writeln!(
+                f,
+                "Server RSS: avg {:.2}MB, peak {:.2}MB",
+                resources.rss_avg as f64 / (1024.0 * 1024.0),
+                resources.rss_peak as f64 / (1024.0 * 1024.0)
+            )?;
+        }
+
+        if let Some(upstream) = self.upstream_requests_per_request {
+            writeln!(f, "Upstream requests per request: {upstream:.2}")?;
+        }
+
+        write!(f, "Requests/sec: {}", self.rps)
+    }
 }
 
-fn parse_u64(data: &str, re: Regex) -> anyhow::Result<u64> {
-    if let Some(caps) = re.captures(data) {
-        let value = &caps[1];
-        Ok(value.parse().context("Failed to parse u64")?)
-    } else {
-        bail!("Failed to parse {:?}", re)
+impl Display for Latency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |us: u64| us as f64 / 1000.0;
+
+        write!(
+            f,
+            "p50 {:.2}ms, p90 {:.2}ms, p99 {:.2}ms, p99.9 {:.2}ms, max {:.2}ms",
+            ms(self.p50),
+            ms(self.p90),
+            ms(self.p99),
+            ms(self.p99_9),
+            ms(self.max)
+        )
+    }
+}
+
+fn check_errors(single_stats: &Stats) -> anyhow::Result<()> {
+    if single_stats.read_errors > 0 {
+        bail!("Execution failed because read_errors exist")
+    }
+
+    if single_stats.write_errors > 0 {
+        bail!("Execution failed because write_errors exist")
+    }
+
+    if single_stats.connect_errors > 0 {
+        bail!("Execution failed because connect_errors exist")
+    }
+
+    if single_stats.status_errors > 0 {
+        bail!("Execution failed because non-2xx responses exist")
     }
+
+    if single_stats.other_errors > 0 {
+        bail!("Execution failed because other_errors exist")
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     mod stats {
+        use std::collections::BTreeMap;
+
         use crate::benchmarks::{AllStats, Stats};
 
         #[test]
@@ -343,38 +724,150 @@ mod tests {
 
             assert_eq!(stats.score(&baseline).unwrap(), 361);
         }
-    }
-}
 
-fn extract_errors(data: &str) -> (u64, u64, u64, u64) {
-    let re = Regex::new(
-        r"Socket\s+errors:\s+connect\s+(\d+),\s+read\s+(\d+).\s+write\s+(\d+).\s+timeout\s+(\d+)",
-    )
-    .unwrap();
-    if let Some(caps) = re.captures(data) {
-        (
-            caps[1].parse().unwrap_or(0),
-            caps[2].parse().unwrap_or(0),
-            caps[3].parse().unwrap_or(0),
-            caps[4].parse().unwrap_or(0),
-        )
-    } else {
-        (0, 0, 0, 0)
-    }
-}
+        #[test]
+        fn test_score_missing_baseline() {
+            let mut stats = AllStats::default();
 
-fn check_errors(single_stats: &Stats) -> anyhow::Result<()> {
-    if single_stats.read_errors > 0 {
-        bail!("Execution failed because read_errors exist")
-    }
+            stats.insert(
+                "posts-title".to_owned(),
+                Stats {
+                    rps: 100,
+                    ..Default::default()
+                },
+            );
+            stats.insert(
+                "users-posts-comments".to_owned(),
+                Stats {
+                    rps: 10,
+                    ..Default::default()
+                },
+            );
 
-    if single_stats.write_errors > 0 {
-        bail!("Execution failed because write_errors exist")
+            let mut baseline = AllStats::default();
+
+            assert!(stats.score(&baseline).is_err());
+
+            baseline.insert(
+                "users-posts-comments".to_owned(),
+                Stats {
+                    rps: 10,
+                    ..Default::default()
+                },
+            );
+
+            assert!(stats.score(&baseline).is_err());
+
+            baseline.insert(
+                "posts-title".to_owned(),
+                Stats {
+                    rps: 50,
+                    ..Default::default()
+                },
+            );
+
+            assert_eq!(stats.score(&baseline).unwrap(), 1500);
+        }
+
+        #[test]
+        fn test_same_dataset() {
+            let dataset: BTreeMap<_, _> =
+                [("users".to_owned(), 10), ("posts".to_owned(), 20)].into();
+            let mut baseline = AllStats::default();
+
+            baseline.insert(
+                "posts-title".to_owned(),
+                Stats {
+                    dataset: Some(dataset.clone()),
+                    ..Default::default()
+                },
+            );
+
+            assert!(baseline.same_dataset(&dataset));
+
+            let mut bigger = dataset.clone();
+            bigger.insert("posts".to_owned(), 1000);
+
+            assert!(!baseline.same_dataset(&bigger));
+
+            baseline.insert("posts-nested".to_owned(), Stats::default());
+
+            assert!(!baseline.same_dataset(&dataset));
+        }
+
+        #[test]
+        fn test_score_invalid() {
+            let mut stats = AllStats::default();
+            let mut baseline = AllStats::default();
+
+            assert!(stats.score(&baseline).is_err());
+
+            stats.insert(
+                "posts-title".to_owned(),
+                Stats {
+                    rps: 100,
+                    ..Default::default()
+                },
+            );
+            baseline.insert("posts-title".to_owned(), Stats::default());
+
+            assert!(stats.score(&baseline).is_err());
+        }
     }
 
-    if single_stats.connect_errors > 0 {
-        bail!("Execution failed because connect_errors exist")
+    mod latency {
+        use hdrhistogram::Histogram;
+
+        use crate::benchmarks::Latency;
+
+        #[test]
+        fn test_latency_percentiles() {
+            let mut histogram = Histogram::<u64>::new_with_bounds(1, 60_000_000, 3).unwrap();
+
+            for value in 1..=1000 {
+                histogram.record(value).unwrap();
+            }
+
+            let latency = Latency::from(&histogram);
+
+            assert_eq!(latency.p50, 500);
+            assert_eq!(latency.p90, 900);
+            assert_eq!(latency.p99, 990);
+            assert_eq!(latency.p99_9, 999);
+            assert_eq!(latency.max, 1000);
+        }
     }
 
-    Ok(())
+    mod aggregate {
+        use crate::{benchmarks::Stats, load_generator::new_histogram, resources::ResourceUsage};
+
+        #[test]
+        fn test_aggregate_runs() {
+            let run = |rps, timeout_errors, cpu_avg, upstream| Stats {
+                rps,
+                timeout_errors,
+                resources: Some(ResourceUsage {
+                    cpu_avg,
+                    ..Default::default()
+                }),
+                upstream_requests_per_request: Some(upstream),
+                ..Default::default()
+            };
+
+            let stats = Stats::aggregate(
+                vec![
+                    run(90, 1, 1.0, 11.0),
+                    run(120, 0, 2.0, 11.0),
+                    run(100, 2, 3.0, 14.0),
+                ],
+                &new_histogram(),
+            );
+
+            assert_eq!(stats.rps, 100);
+            assert_eq!(stats.timeout_errors, 3);
+            assert_eq!(stats.resources.unwrap().cpu_avg, 3.0);
+            assert_eq!(stats.rps_spread.unwrap().samples, [90, 120, 100]);
+            assert_eq!(stats.upstream_requests_per_request, Some(12.0));
+        }
+    }
 }
@@ -1,7 +1,4 @@
-use std::{
-    path::Path,
-    process::{Output, Stdio},
-};
+use std::path::Path;
 
 use anyhow::{anyhow, Result};
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
@@ -13,6 +10,7 @@ pub struct Command {
 
 pub struct CommandInstance {
     child: AsyncGroupChild,
+    pgid: Option<u32>,
 }
 
 impl Drop for CommandInstance {
@@ -23,7 +21,11 @@ impl Drop for CommandInstance {
 
 impl From<AsyncGroupChild> for CommandInstance {
     fn from(child: AsyncGroupChild) -> Self {
-        Self { child }
+        // the id is not available after the process is reaped
+        // so remember it for the whole lifetime of the group
+        let pgid = child.id();
+
+        Self { child, pgid }
     }
 }
 
@@ -47,13 +49,13 @@ impl Command {
 
         let mut command = tokio::process::Command::new(cmd_path);
 
-        command.current_dir(&cmd_path.parent().unwrap_or(cmd_path));
+        command.current_dir(cmd_path.parent().unwrap_or(cmd_path));
 
         Ok(Self { command })
     }
 
-    pub fn args(&mut self, args: &[&str]) {
-        self.command.args(args);
+    pub fn envs<'a>(&mut self, vars: impl IntoIterator<Item = (&'a str, String)>) {
+        self.command.envs(vars);
     }
 
     pub fn run(&mut self) -> Result<CommandInstance> {
@@ -64,29 +66,42 @@ impl Command {
         Ok(child.into())
     }
 
-    pub async fn run_and_capture(&mut self) -> Result<Output> {
-        info!("Run process in background and capture its output");
-
-        self.command.stdout(Stdio::piped());
-        self.command.stderr(Stdio::piped());
+    pub async fn run_and_wait(&mut self) -> Result<()> {
+        info!("Output logs from setup script below");
 
-        let output = self.command.group_output().await?;
+        let status = self.command.group_spawn()?.wait().await?;
 
-        if output.status.success() {
-            Ok(output)
+        if status.success() {
+            Ok(())
         } else {
-            info!("Stdout is:\n {}", String::from_utf8_lossy(&output.stdout));
-            info!("Stderr is:\n {}", String::from_utf8_lossy(&output.stderr));
-
             Err(anyhow!(
                 "Process failed with exit code: {}",
-                output.status.code().unwrap_or(0)
+                status.code().unwrap_or(0)
             ))
         }
     }
 }
 
 impl CommandInstance {
+    /// Id of the spawned process that is also the id of its process group
+    pub fn id(&self) -> Option<u32> {
+        self.pgid
+    }
+
+    /// Checks whether every process of the group has finished.
+    /// The script itself may exit earlier if it leaves the server running
+    pub fn has_exited(&mut self) -> bool {
+        // reap the group leader so it doesn't stay as a zombie
+        let _ = self.child.try_wait();
+
+        let Some(pgid) = self.pgid else {
+            return true;
+        };
+
+        // SAFETY: signal 0 only checks that the group exists
+        unsafe { libc::kill(-(pgid as libc::pid_t), 0) != 0 }
+    }
+
     pub async fn kill(mut self) -> Result<()> {
         Ok(self.child.kill().await?)
     }
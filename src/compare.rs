@@ -0,0 +1,374 @@
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+/// Rules of comparison between expected and actual responses.
+/// Unset options are taken from `tests/compare.json` and then from the defaults
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub(crate) struct CompareOptions {
+    /// Highest allowed absolute difference between floats, `0` by default
+    float_tolerance: Option<f64>,
+    /// Lists of `data` to compare regardless of the order of items, `false` by default
+    ignore_list_order: Option<ListOrder>,
+    /// Compare only paths of the errors since messages are specific
+    /// to the server, `true` by default
+    normalize_errors: Option<bool>,
+    /// Ignore `extensions` of the response and errors, `true` by default
+    ignore_extensions: Option<bool>,
+}
+
+/// Lists whose order is not defined by the schema
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum ListOrder {
+    /// Every list of `data` or none of them
+    All(bool),
+    /// Only the lists of the fields with these names
+    Fields(Vec<String>),
+}
+
+impl ListOrder {
+    fn is_ignored(&self, field: &str) -> bool {
+        match self {
+            ListOrder::All(ignored) => *ignored,
+            ListOrder::Fields(fields) => fields.iter().any(|name| name == field),
+        }
+    }
+}
+
+impl CompareOptions {
+    /// Fills unset options from the fallback
+    pub(crate) fn or(self, fallback: &CompareOptions) -> Self {
+        Self {
+            float_tolerance: self.float_tolerance.or(fallback.float_tolerance),
+            ignore_list_order: self
+                .ignore_list_order
+                .or_else(|| fallback.ignore_list_order.clone()),
+            normalize_errors: self.normalize_errors.or(fallback.normalize_errors),
+            ignore_extensions: self.ignore_extensions.or(fallback.ignore_extensions),
+        }
+    }
+
+    fn float_tolerance(&self) -> f64 {
+        self.float_tolerance.unwrap_or(0.0)
+    }
+
+    fn ignore_list_order(&self) -> &ListOrder {
+        self.ignore_list_order
+            .as_ref()
+            .unwrap_or(&ListOrder::All(false))
+    }
+
+    fn normalize_errors(&self) -> bool {
+        self.normalize_errors.unwrap_or(true)
+    }
+
+    fn ignore_extensions(&self) -> bool {
+        self.ignore_extensions.unwrap_or(true)
+    }
+}
+
+/// Applies the options to both responses so the rest of differences
+/// can be shown with a plain diff
+pub(crate) fn normalize_responses(
+    expected: &Value,
+    actual: &Value,
+    options: &CompareOptions,
+) -> (Value, Value) {
+    let mut expected = normalize_response(expected, options);
+    let mut actual = normalize_response(actual, options);
+
+    // floats are paired by the index before the lists are reordered,
+    // so the tolerance applies only to items that come in the same order
+    align_floats(&expected, &mut actual, options.float_tolerance());
+
+    for response in [&mut expected, &mut actual] {
+        if let Some(data) = response.get_mut("data") {
+            sort_lists(data, "data", options.ignore_list_order());
+        }
+    }
+
+    (expected, actual)
+}
+
+/// Keeps only the parts of the response that should match between servers.
+/// Missing `data` is the same as `null`
+fn normalize_response(response: &Value, options: &CompareOptions) -> Value {
+    let Value::Object(response) = response else {
+        return response.clone();
+    };
+
+    let mut normalized = Map::new();
+
+    normalized.insert(
+        "data".to_owned(),
+        response.get("data").cloned().unwrap_or(Value::Null),
+    );
+
+    let errors = response
+        .get("errors")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let errors = if options.normalize_errors() {
+        normalize_errors(errors)
+    } else if options.ignore_extensions() {
+        errors
+            .iter()
+            .map(|error| without_extensions(error.clone()))
+            .collect()
+    } else {
+        errors.to_vec()
+    };
+
+    if !errors.is_empty() {
+        normalized.insert("errors".to_owned(), Value::Array(errors));
+    }
+
+    if !options.ignore_extensions() {
+        if let Some(extensions) = response.get("extensions") {
+            normalized.insert("extensions".to_owned(), extensions.clone());
+        }
+    }
+
+    Value::Object(normalized)
+}
+
+/// Reduces errors to their paths
+fn normalize_errors(errors: &[Value]) -> Vec<Value> {
+    // servers may report a different number of errors for the same field
+    let mut error_paths: Vec<Value> = errors
+        .iter()
+        .map(|error| error.get("path").cloned().unwrap_or(Value::Null))
+        .collect();
+
+    error_paths.sort_by_key(Value::to_string);
+    error_paths.dedup();
+
+    error_paths
+        .into_iter()
+        .map(|path| json!({ "path": path }))
+        .collect()
+}
+
+fn without_extensions(mut error: Value) -> Value {
+    if let Value::Object(error) = &mut error {
+        error.remove("extensions");
+    }
+
+    error
+}
+
+/// Sorts the lists of the fields with undefined order,
+/// nested lists belong to the same field
+fn sort_lists(value: &mut Value, field: &str, order: &ListOrder) {
+    match value {
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                sort_lists(item, field, order);
+            }
+
+            if order.is_ignored(field) {
+                items.sort_by_cached_key(Value::to_string);
+            }
+        }
+        Value::Object(fields) => {
+            for (field, value) in fields.iter_mut() {
+                sort_lists(value, field, order);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces numbers of the actual response with the expected ones
+/// if they are equal, e.g. `1` and `1.0`, or if both are floats
+/// that differ less than the tolerance. Integers like ids never match
+/// under the tolerance
+fn align_floats(expected: &Value, actual: &mut Value, tolerance: f64) {
+    match (expected, actual) {
+        (Value::Array(expected), Value::Array(actual)) => {
+            for (expected, actual) in expected.iter().zip(actual) {
+                align_floats(expected, actual, tolerance);
+            }
+        }
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                if let Some(actual) = actual.get_mut(key) {
+                    align_floats(expected, actual, tolerance);
+                }
+            }
+        }
+        (Value::Number(expected), actual @ Value::Number(_)) => {
+            let floats = expected.is_f64() && actual.as_number().is_some_and(|n| n.is_f64());
+            let close = matches!(
+                (expected.as_f64(), actual.as_f64()),
+                (Some(a), Some(b)) if a == b || (floats && (a - b).abs() <= tolerance)
+            );
+
+            if close {
+                *actual = Value::Number(expected.clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{normalize_responses, CompareOptions};
+
+    fn compare(expected: serde_json::Value, actual: serde_json::Value, options: &str) -> bool {
+        let options: CompareOptions = serde_json::from_str(options).unwrap();
+        let (expected, actual) = normalize_responses(&expected, &actual, &options);
+
+        expected == actual
+    }
+
+    #[test]
+    fn test_normalize_errors() {
+        let reference = json!({
+            "data": { "post": { "id": 1 }, "missing": null },
+            "errors": [{
+                "message": "IOException: 404 Not Found",
+                "locations": [{ "line": 1, "column": 30 }],
+                "path": ["missing"]
+            }]
+        });
+        let tested = json!({
+            "errors": [
+                { "message": "Post not found", "path": ["missing"], "extensions": { "code": 404 } },
+                { "message": "Upstream failed", "path": ["missing"] }
+            ],
+            "data": { "post": { "id": 1 }, "missing": null }
+        });
+
+        assert!(compare(reference.clone(), tested.clone(), "{}"));
+        assert!(!compare(
+            reference,
+            tested,
+            r#"{ "normalizeErrors": false }"#
+        ));
+    }
+
+    #[test]
+    fn test_normalize_missing_data() {
+        assert!(compare(
+            json!({ "errors": [{ "message": "Unknown field" }] }),
+            json!({ "data": null, "errors": [{ "message": "Invalid" }] }),
+            "{}"
+        ));
+        assert!(!compare(
+            json!({ "data": { "user": null } }),
+            json!({ "data": { "user": null }, "errors": [{ "path": ["user"] }] }),
+            "{}"
+        ));
+    }
+
+    #[test]
+    fn test_ignore_extensions() {
+        let expected = json!({
+            "data": null,
+            "errors": [{ "message": "Not found", "path": ["user"] }]
+        });
+        let actual = json!({
+            "data": null,
+            "errors": [{ "message": "Not found", "path": ["user"], "extensions": { "code": 404 } }],
+            "extensions": { "cost": 1 }
+        });
+
+        assert!(compare(
+            expected.clone(),
+            actual.clone(),
+            r#"{ "normalizeErrors": false }"#
+        ));
+        assert!(!compare(
+            expected,
+            actual,
+            r#"{ "normalizeErrors": false, "ignoreExtensions": false }"#
+        ));
+    }
+
+    #[test]
+    fn test_float_tolerance() {
+        let expected = json!({ "data": { "geo": [{ "lat": 10.1234, "lng": 1 }] } });
+        let actual = json!({ "data": { "geo": [{ "lat": 10.12341, "lng": 1.0 }] } });
+
+        assert!(!compare(expected.clone(), actual.clone(), "{}"));
+        assert!(compare(
+            expected.clone(),
+            actual,
+            r#"{ "floatTolerance": 0.0001 }"#
+        ));
+        assert!(!compare(
+            expected,
+            json!({ "data": { "geo": [{ "lat": 10.2, "lng": 1 }] } }),
+            r#"{ "floatTolerance": 0.0001 }"#
+        ));
+    }
+
+    #[test]
+    fn test_float_tolerance_integers() {
+        let expected = json!({ "data": { "user": { "id": 1, "lat": 1.5 } } });
+
+        assert!(!compare(
+            expected.clone(),
+            json!({ "data": { "user": { "id": 2, "lat": 1.5 } } }),
+            r#"{ "floatTolerance": 1 }"#
+        ));
+        assert!(!compare(
+            expected.clone(),
+            json!({ "data": { "user": { "id": 1.5, "lat": 1.5 } } }),
+            r#"{ "floatTolerance": 1 }"#
+        ));
+        assert!(compare(
+            expected,
+            json!({ "data": { "user": { "id": 1.0, "lat": 2.0 } } }),
+            r#"{ "floatTolerance": 1 }"#
+        ));
+    }
+
+    #[test]
+    fn test_ignore_list_order() {
+        let expected = json!({ "data": { "posts": [{ "id": 1 }, { "id": 2 }] } });
+        let actual = json!({ "data": { "posts": [{ "id": 2 }, { "id": 1 }] } });
+
+        assert!(!compare(expected.clone(), actual.clone(), "{}"));
+        assert!(compare(
+            expected.clone(),
+            actual.clone(),
+            r#"{ "ignoreListOrder": true }"#
+        ));
+        assert!(compare(
+            expected.clone(),
+            actual.clone(),
+            r#"{ "ignoreListOrder": ["posts"] }"#
+        ));
+        assert!(!compare(
+            expected,
+            actual,
+            r#"{ "ignoreListOrder": ["comments"] }"#
+        ));
+
+        // paths of errors keep their order
+        let expected = json!({ "data": null, "errors": [{ "path": ["posts", 0] }] });
+        let actual = json!({ "data": null, "errors": [{ "path": [0, "posts"] }] });
+
+        assert!(!compare(expected, actual, r#"{ "ignoreListOrder": true }"#));
+    }
+
+    #[test]
+    fn test_options_fallback() {
+        let global: CompareOptions =
+            serde_json::from_str(r#"{ "floatTolerance": 0.1, "ignoreListOrder": true }"#).unwrap();
+        let options: CompareOptions = serde_json::from_str(r#"{ "floatTolerance": 0.5 }"#).unwrap();
+        let options = options.or(&global);
+
+        assert_eq!(options.float_tolerance(), 0.5);
+        assert!(options.ignore_list_order().is_ignored("posts"));
+        assert!(options.normalize_errors());
+    }
+}
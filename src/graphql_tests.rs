@@ -1,67 +1,239 @@
-use std::{fs, sync::LazyLock};
+use std::time::Instant;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use diff_logger::DiffLogger;
 use reqwest::Method;
+use serde_json::{json, Value};
 use tracing::{error, info};
 
-use crate::request::{MOCK_API_CLIENT, REFERENCE_GRAPHQL_CLIENT, TESTED_GRAPHQL_CLIENT};
+use crate::{
+    compare::normalize_responses,
+    request::{GraphqlClient, GraphqlRequest, RestClient},
+    scenarios::{Scenario, SCENARIOS},
+    snapshots::{load_snapshot, restore_dataset},
+    test_report::{TestCase, TestFailure, TestSuite},
+    utils::env_default,
+};
+
+pub(crate) const NUMBER_OF_TESTS: usize = 5;
+
+/// Source of the expected responses
+pub(crate) enum Expected<'a> {
+    /// Request the reference server with freshly generated data
+    Reference(&'a GraphqlClient),
+    /// Use responses recorded with `record_snapshots` and their data
+    Snapshots,
+}
 
-use super::ROOT_DIR;
+/// Seed of the mock data for the first test iteration,
+/// every next iteration uses the next number
+pub(crate) fn base_seed() -> u64 {
+    env_default("TEST_SEED", rand::random::<u32>().into())
+}
 
-const NUMBER_OF_TESTS: usize = 5;
+/// Regenerates the mock data from the seed
+pub(crate) async fn reset_mock_data(mock_api: &RestClient, seed: u64) -> Result<()> {
+    mock_api
+        .request_json(Method::POST, "reset", &json!({ "seed": seed }))
+        .await?;
 
-static TESTS: LazyLock<Result<Vec<String>>> = LazyLock::new(|| {
-    let tests_path = format!("{ROOT_DIR}/tests");
-    let mut tests = Vec::new();
+    Ok(())
+}
 
-    for entry in fs::read_dir(tests_path)? {
-        let path = entry?.path();
+/// Makes the mock server fail as described in the scenario.
+/// Returns the previous faults to restore them afterwards
+pub(crate) async fn replace_faults(mock_api: &RestClient, faults: &Value) -> Result<Value> {
+    let config = mock_api.request(Method::GET, "admin").await?;
 
-        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-            if ext == "graphql" {
-                tests.push(fs::read_to_string(&path)?);
-            }
-        }
-    }
+    mock_api
+        .request_json(Method::PATCH, "admin", &json!({ "faults": faults }))
+        .await
+        .context("Failed to inject faults into the mock server")?;
 
-    Ok(tests)
-});
+    Ok(config["faults"].clone())
+}
+
+/// Brings the mock data to the initial state of the iteration,
+/// the data is regenerated from the seed or restored from the snapshots if there is no seed
+async fn prepare_mock_data(
+    mock_api: &RestClient,
+    iteration: usize,
+    seed: Option<u64>,
+) -> Result<()> {
+    match seed {
+        Some(seed) => reset_mock_data(mock_api, seed).await,
+        None => restore_dataset(iteration, mock_api).await,
+    }
+}
 
-pub async fn run_graphql_tests() -> Result<()> {
+/// Runs every test in every iteration and collects the failures into the suite.
+/// Fails only if the expected responses or the mock data are not available
+pub(crate) async fn run_graphql_tests(
+    mut suite: TestSuite,
+    tested: &GraphqlClient,
+    expected: &Expected<'_>,
+    mock_api: &RestClient,
+) -> Result<TestSuite> {
     info!("Run graphql assert tests");
 
-    let tests = TESTS
+    let tests = SCENARIOS
         .as_ref()
-        .map_err(|e| anyhow!("Failed to resolve tests due to error: {e}"))?;
+        .map_err(|e| anyhow!("Failed to resolve tests due to error: {e:#}"))?;
+
+    let base_seed = base_seed();
 
     for i in 1..NUMBER_OF_TESTS {
-        info!("Test iteration: {i}");
+        let seed = match expected {
+            Expected::Reference(_) => {
+                let seed = base_seed + i as u64 - 1;
 
-        MOCK_API_CLIENT.request(Method::POST, "reset").await?;
+                info!("Test iteration: {i}, mock data seed: {seed}");
 
-        for test in tests {
-            let actual = TESTED_GRAPHQL_CLIENT.request(&test).await?;
+                Some(seed)
+            }
+            Expected::Snapshots => {
+                info!("Test iteration: {i}");
 
-            let expected = REFERENCE_GRAPHQL_CLIENT.request(&test).await?;
+                None
+            }
+        };
 
-            let differ = DiffLogger::new();
+        prepare_mock_data(mock_api, i, seed).await?;
 
-            let difference = differ.diff(&expected, &actual);
+        for test in tests {
+            let started = Instant::now();
+            let failure = run_test(test, i, seed, tested, expected, mock_api).await?;
 
-            if !difference.is_empty() {
-                error!(
-                    "Actual response is not equal to expected
-    Note: left is expected response -> right is actual response"
-                );
-                println!("{}", difference);
+            if let Some(failure) = &failure {
+                error!("Test `{}` failed: {}", test.name, failure.message);
 
-                return Err(anyhow!("Actual response is not equal to expected"));
+                if let Some(seed) = seed {
+                    error!(
+                        "Run with `TEST_SEED={seed}` to reproduce the failure with the same data"
+                    );
+                }
             }
+
+            suite.push(TestCase {
+                name: test.name.clone(),
+                iteration: i,
+                seed,
+                time: started.elapsed().as_secs_f64(),
+                failure,
+            });
         }
     }
 
-    info!("Execution of graphql tests finished");
+    info!(
+        "Execution of graphql tests finished, {} of {} tests failed",
+        suite.failures, suite.tests
+    );
 
-    Ok(())
+    Ok(suite)
+}
+
+async fn run_test(
+    test: &Scenario,
+    iteration: usize,
+    seed: Option<u64>,
+    tested: &GraphqlClient,
+    expected: &Expected<'_>,
+    mock_api: &RestClient,
+) -> Result<Option<TestFailure>> {
+    // the scenario changes the data, so both servers have to start
+    // with the initial data and the next tests have to get it back
+    if test.mutates {
+        prepare_mock_data(mock_api, iteration, seed).await?;
+    }
+
+    // both servers get the same faults from the mock server
+    let previous_faults = match &test.faults {
+        Some(faults) => Some(replace_faults(mock_api, faults).await?),
+        None => None,
+    };
+
+    let responses = async {
+        let actual = run_steps(tested, &test.steps).await;
+
+        if test.mutates {
+            prepare_mock_data(mock_api, iteration, seed).await?;
+        }
+
+        let expected = match expected {
+            Expected::Reference(reference) => run_steps(reference, &test.steps).await?,
+            Expected::Snapshots => load_snapshot(iteration, test).await?,
+        };
+
+        anyhow::Ok((actual, expected))
+    }
+    .await;
+
+    // the faults are restored even if the expected responses are not available,
+    // otherwise they would break the rest of the tests
+    if let Some(previous) = previous_faults {
+        replace_faults(mock_api, &previous).await?;
+    }
+
+    let (actual, expected) = responses?;
+
+    if test.mutates {
+        prepare_mock_data(mock_api, iteration, seed).await?;
+    }
+
+    let actual = match actual {
+        Ok(actual) => actual,
+        Err(error) => {
+            return Ok(Some(TestFailure::new(
+                format!("Request failed: {error:#}"),
+                None,
+            )))
+        }
+    };
+
+    let differ = DiffLogger::new();
+    let mut differences = Vec::new();
+
+    for (step, (expected, actual)) in expected.iter().zip(&actual).enumerate() {
+        let (expected, actual) = normalize_responses(expected, actual, &test.compare);
+
+        let difference = differ.diff(&expected, &actual);
+
+        if difference.is_empty() {
+            continue;
+        }
+
+        if test.steps.len() > 1 {
+            differences.push(format!("Step {}:\n{difference}", step + 1));
+        } else {
+            differences.push(difference);
+        }
+    }
+
+    if differences.is_empty() {
+        return Ok(None);
+    }
+
+    let difference = differences.join("\n");
+
+    error!(
+        "Actual response is not equal to expected
+    Note: left is expected response -> right is actual response"
+    );
+    println!("{}", difference);
+
+    Ok(Some(TestFailure::new(
+        "Actual response is not equal to expected",
+        Some(&difference),
+    )))
+}
+
+/// Sends the requests one after another
+async fn run_steps(client: &GraphqlClient, steps: &[GraphqlRequest]) -> Result<Vec<Value>> {
+    let mut responses = Vec::with_capacity(steps.len());
+
+    for step in steps {
+        responses.push(client.request(step).await?);
+    }
+
+    Ok(responses)
 }
@@ -1,10 +1,8 @@
-use std::convert::identity;
-
 use anyhow::{Context, Result};
 use octocrate::{APIConfig, GitHubAPI, PersonalAccessToken};
 use regex::Regex;
 use tokio::task::JoinSet;
-use tracing::{error, info};
+use tracing::info;
 
 const OWNER: &str = "tailcallhq";
 const REPO: &str = "hackathon";
@@ -16,7 +14,9 @@ struct Score {
     score: u64,
 }
 
-async fn run() -> Result<()> {
+/// Collects scores from benchmark comments of hackathon pull requests
+/// and publishes them to the leaderboard issue
+pub async fn update_leaderboard() -> Result<()> {
     let score_regex = Regex::new(r"The score is: \*\*(\d+)\*\*")?;
 
     let token = PersonalAccessToken::new(
@@ -82,7 +82,7 @@ async fn run() -> Result<()> {
         .into_iter()
         .collect::<Result<Vec<_>>>()?
         .into_iter()
-        .filter_map(identity)
+        .flatten()
         .collect();
 
     scores.push(Score {
@@ -110,13 +110,3 @@ async fn run() -> Result<()> {
 
     Ok(())
 }
-
-#[tokio::main]
-async fn main() {
-    tracing_subscriber::fmt::init();
-
-    if let Err(error) = run().await {
-        error!("Critical error: {:#}", error);
-        panic!("Critical error");
-    }
-}
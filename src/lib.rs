@@ -1,8 +1,22 @@
 mod benchmarks;
 mod command;
+mod compare;
 mod graphql_tests;
+pub mod leaderboard;
+mod load_generator;
+mod manifest;
+pub mod mocks;
+mod ports;
 pub mod project;
+pub mod regression;
+pub mod report;
 mod request;
+mod resources;
+mod scenarios;
+mod schema_check;
+mod snapshots;
+mod statistics;
+mod test_report;
 mod utils;
 
 pub const ROOT_DIR: &str = env!("CARGO_MANIFEST_DIR");
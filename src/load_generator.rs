@@ -0,0 +1,282 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use bytes::Bytes;
+use hdrhistogram::Histogram;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONNECTION, CONTENT_TYPE};
+use tokio::task::JoinSet;
+use tracing::info;
+
+/// Pause after a failed request, so a server that is down
+/// doesn't make the connections spin on errors
+const ERROR_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Settings of a single load run
+pub struct LoadConfig {
+    pub url: String,
+    pub body: Bytes,
+    pub headers: Vec<(String, String)>,
+    pub duration: Duration,
+    pub connections: usize,
+    pub timeout: Duration,
+}
+
+/// Raw counters collected during the load run
+pub struct LoadResult {
+    pub requests: u64,
+    pub bytes_read: u64,
+    pub connect_errors: u64,
+    pub read_errors: u64,
+    pub write_errors: u64,
+    pub timeout_errors: u64,
+    pub status_errors: u64,
+    pub other_errors: u64,
+    pub elapsed: Duration,
+    /// Latency of the requests that got a response in microseconds,
+    /// failed ones are only counted in the error counters
+    pub latency: Histogram<u64>,
+}
+
+impl LoadResult {
+    pub fn rps(&self) -> u64 {
+        let secs = self.elapsed.as_secs_f64();
+
+        if secs > 0.0 {
+            (self.requests as f64 / secs) as u64
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    bytes_read: AtomicU64,
+    connect_errors: AtomicU64,
+    read_errors: AtomicU64,
+    write_errors: AtomicU64,
+    timeout_errors: AtomicU64,
+    status_errors: AtomicU64,
+    other_errors: AtomicU64,
+}
+
+impl Counters {
+    fn record_error(&self, error: &reqwest::Error) {
+        let counter = if error.is_timeout() {
+            &self.timeout_errors
+        } else if error.is_connect() {
+            &self.connect_errors
+        } else if error.is_body() || error.is_decode() {
+            &self.read_errors
+        } else if error.is_request() {
+            &self.write_errors
+        } else {
+            &self.other_errors
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Sends the configured request over `connections` keep-alive
+/// connections in a closed loop until `duration` elapses
+pub async fn run_load(config: &LoadConfig) -> Result<LoadResult> {
+    info!(
+        "Running {}s test @ {} with {} connections",
+        config.duration.as_secs(),
+        config.url,
+        config.connections
+    );
+
+    let mut headers = HeaderMap::new();
+
+    headers.insert(CONNECTION, HeaderValue::from_static("keep-alive"));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    for (name, value) in &config.headers {
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+    }
+
+    let client = reqwest::Client::builder()
+        .default_headers(headers)
+        .pool_max_idle_per_host(config.connections)
+        .timeout(config.timeout)
+        .build()?;
+
+    let counters = Arc::new(Counters::default());
+    let start = Instant::now();
+    let deadline = start + config.duration;
+    let mut join_set = JoinSet::new();
+
+    for _ in 0..config.connections {
+        let client = client.clone();
+        let counters = counters.clone();
+        let url = config.url.clone();
+        let body = config.body.clone();
+
+        join_set.spawn(async move {
+            let mut latency = new_histogram();
+
+            // requests still in flight at the deadline are dropped
+            // so they don't stretch the measured interval
+            let _ = tokio::time::timeout_at(deadline.into(), async {
+                loop {
+                    let request_start = Instant::now();
+                    let result = match client.post(&url).body(body.clone()).send().await {
+                        Ok(response) => {
+                            let status = response.status();
+
+                            response.bytes().await.map(|bytes| (status, bytes))
+                        }
+                        Err(error) => Err(error),
+                    };
+
+                    match result {
+                        Ok((status, bytes)) => {
+                            latency.saturating_record(request_start.elapsed().as_micros() as u64);
+                            counters.requests.fetch_add(1, Ordering::Relaxed);
+                            counters
+                                .bytes_read
+                                .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+
+                            if !status.is_success() {
+                                counters.status_errors.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                        Err(error) => {
+                            counters.record_error(&error);
+                            tokio::time::sleep(ERROR_BACKOFF).await;
+                        }
+                    }
+                }
+            })
+            .await;
+
+            latency
+        });
+    }
+
+    let mut latency = new_histogram();
+
+    while let Some(result) = join_set.join_next().await {
+        latency.add(result?)?;
+    }
+
+    let elapsed = start.elapsed();
+
+    Ok(LoadResult {
+        requests: counters.requests.load(Ordering::Relaxed),
+        bytes_read: counters.bytes_read.load(Ordering::Relaxed),
+        connect_errors: counters.connect_errors.load(Ordering::Relaxed),
+        read_errors: counters.read_errors.load(Ordering::Relaxed),
+        write_errors: counters.write_errors.load(Ordering::Relaxed),
+        timeout_errors: counters.timeout_errors.load(Ordering::Relaxed),
+        status_errors: counters.status_errors.load(Ordering::Relaxed),
+        other_errors: counters.other_errors.load(Ordering::Relaxed),
+        elapsed,
+        latency,
+    })
+}
+
+pub fn new_histogram() -> Histogram<u64> {
+    // 1us..60s with 3 significant digits
+    Histogram::new_with_bounds(1, 60_000_000, 3).expect("valid histogram bounds")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{http::StatusCode, routing::post, Router};
+    use tokio::net::TcpListener;
+
+    use super::{run_load, LoadConfig};
+
+    async fn serve(router: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        format!("http://{addr}/graphql")
+    }
+
+    fn config(url: String) -> LoadConfig {
+        LoadConfig {
+            url,
+            body: r#"{"query":"{ users { id } }"}"#.into(),
+            headers: vec![],
+            duration: Duration::from_millis(300),
+            connections: 2,
+            timeout: Duration::from_millis(100),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_load() {
+        let router = Router::new().route(
+            "/graphql",
+            post(|body: String| async move {
+                tokio::time::sleep(Duration::from_millis(2)).await;
+                body
+            }),
+        );
+        let result = run_load(&config(serve(router).await)).await.unwrap();
+
+        assert!(result.requests > 0);
+        assert_eq!(result.latency.len(), result.requests);
+        assert!(result.latency.min() >= 2000);
+        assert_eq!(result.status_errors, 0);
+        assert_eq!(result.connect_errors, 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_load_errors() {
+        let router = Router::new()
+            .route("/graphql", post(|| async { StatusCode::BAD_GATEWAY }))
+            .route(
+                "/slow",
+                post(|| async { tokio::time::sleep(Duration::from_secs(1)).await }),
+            );
+        let url = serve(router).await;
+
+        let result = run_load(&config(url.clone())).await.unwrap();
+
+        assert_eq!(result.status_errors, result.requests);
+        assert_eq!(result.latency.len(), result.requests);
+
+        let result = run_load(&config(url.replace("graphql", "slow")))
+            .await
+            .unwrap();
+
+        assert_eq!(result.requests, 0);
+        assert!(result.timeout_errors > 0);
+        assert!(result.latency.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_load_server_down() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+
+        drop(listener);
+
+        let result = run_load(&config(url)).await.unwrap();
+
+        assert_eq!(result.requests, 0);
+        assert!(result.connect_errors > 0);
+        // the backoff keeps every connection to about one attempt per 10ms
+        assert!(result.connect_errors <= 2 * 31);
+        assert!(result.latency.is_empty());
+    }
+}
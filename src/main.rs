@@ -1,42 +1,251 @@
-use std::fs;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
-use clap::Parser;
-use tracing::{error, info};
+use anyhow::{bail, Result};
+use clap::{Args, Parser, Subcommand};
+use futures::{stream, StreamExt, TryStreamExt};
+use tracing::{error, info, warn};
 
-use hackathon::project::Project;
+use hackathon::leaderboard::update_leaderboard;
+use hackathon::mocks::generate_mocks;
+use hackathon::project::{Project, Stage};
+use hackathon::regression::{compare_results, Thresholds};
+use hackathon::report::{ProjectReport, Summary};
 use hackathon::ROOT_DIR;
 
 #[derive(Parser, Debug)]
-struct Args {
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Arguments for `run` when no subcommand is specified
+    #[command(flatten)]
+    run: ProjectArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Run tests, benchmarks and tests again for projects
+    Run(ProjectArgs),
+    /// Run only the correctness tests for projects
+    Test(ProjectArgs),
+    /// Run only the benchmarks for projects
+    Bench(ProjectArgs),
+    /// Generate baseline stats with the reference implementation
+    Baseline,
+    /// Record responses of the reference implementation for offline tests
+    Record,
+    /// Generate json files with mock data
+    Mocks,
+    /// Update the leaderboard issue with scores from pull requests
+    Leaderboard,
+    /// Check the project setup without starting it
+    Validate {
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Compare benchmark results of the project with the previous run
+    Compare(CompareArgs),
+}
+
+#[derive(Args, Debug)]
+struct ProjectArgs {
     #[arg(long)]
     project: Option<String>,
+
+    /// Number of projects to run at the same time,
+    /// parallel benchmarks compete for CPU
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Run every project even if some of them fail
+    /// and write the summary to `results/summary.{json,md}`
+    #[arg(long)]
+    keep_going: bool,
+
+    /// Compare responses with the recorded snapshots
+    /// instead of running the reference server
+    #[arg(long)]
+    offline: bool,
 }
 
-async fn run() -> Result<()> {
-    let args = Args::parse();
+#[derive(Args, Debug)]
+struct CompareArgs {
+    #[arg(long)]
+    project: String,
+
+    /// Results directory to compare against,
+    /// defaults to `results/<project>/previous`
+    #[arg(long)]
+    against: Option<PathBuf>,
+
+    /// Allowed drop of rps in percents
+    #[arg(long, default_value_t = 5.0)]
+    threshold: f64,
+
+    /// Allowed growth of p99 latency in percents
+    #[arg(long, default_value_t = 10.0)]
+    latency_threshold: f64,
+}
+
+/// Loads the projects by the directory name, a project with an invalid
+/// `project.toml` is returned as an error to not abort the other projects
+fn load_projects(only_project: Option<&str>) -> Result<Vec<(String, Result<Project>)>> {
     let projects_dir = format!("{ROOT_DIR}/projects");
+    let mut projects = Vec::new();
 
     for entry in fs::read_dir(projects_dir)? {
         let path = entry?.path();
 
         if path.is_dir() {
-            let project = Project::new(path)?;
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
 
-            if let Some(only_project) = &args.project {
-                if project.name() != only_project {
-                    info!("Ignore project: {}", project.name());
+            if let Some(only_project) = only_project {
+                if name != only_project {
+                    info!("Ignore project: {name}");
                     continue;
                 }
             }
 
-            project.run_project().await?;
+            projects.push((name, Project::new(path)));
+        }
+    }
+
+    Ok(projects)
+}
+
+async fn run_projects(args: ProjectArgs, stage: Stage) -> Result<()> {
+    if args.jobs > 1 && stage.has_benchmarks() {
+        warn!(
+            "Parallel benchmarks compete for CPU, their results are not comparable to the baseline"
+        );
+    }
+
+    let mut projects = load_projects(args.project.as_deref())?;
+
+    // without `--keep-going` fail before running anything
+    if !args.keep_going {
+        projects = projects
+            .into_iter()
+            .map(|(name, project)| Ok((name, Ok(project?))))
+            .collect::<Result<_>>()?;
+    }
+
+    let runs = stream::iter(projects)
+        .map(|(name, project)| async move {
+            let result = match project {
+                Ok(project) => project.run_stage(stage, args.offline).await,
+                Err(error) => Err(error),
+            };
+
+            (name, result)
+        })
+        .buffer_unordered(args.jobs.max(1));
+
+    if !args.keep_going {
+        runs.map(|(_, result)| result)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        return Ok(());
+    }
+
+    let mut projects: Vec<_> = runs
+        .map(|(name, result)| {
+            if let Err(error) = &result {
+                error!("Project `{name}` failed: {error:#}");
+            }
+
+            ProjectReport::new(name, &result)
+        })
+        .collect()
+        .await;
+
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let summary = Summary { projects };
+
+    summary.write(&Path::new(ROOT_DIR).join("results")).await?;
+
+    info!("Projects summary:\n\n{}", summary.to_markdown());
+
+    let failed = summary.failed();
+
+    if failed > 0 {
+        bail!("{failed} of {} projects failed", summary.projects.len());
+    }
+
+    Ok(())
+}
+
+fn validate(only_project: Option<&str>) -> Result<()> {
+    let mut failed = 0;
+
+    for (name, project) in load_projects(only_project)? {
+        match project.and_then(|project| project.validate()) {
+            Ok(()) => info!("Project `{name}` is valid"),
+            Err(error) => {
+                error!("Project `{name}` is invalid: {error:#}");
+                failed += 1;
+            }
         }
     }
 
+    if failed > 0 {
+        bail!("{failed} projects are invalid");
+    }
+
     Ok(())
 }
 
+async fn compare(args: CompareArgs) -> Result<()> {
+    let results_path = Path::new(ROOT_DIR).join("results").join(&args.project);
+    let against = args
+        .against
+        .unwrap_or_else(|| results_path.join("previous"));
+
+    compare_results(
+        &results_path,
+        &against,
+        Thresholds {
+            rps: args.threshold,
+            latency: args.latency_threshold,
+        },
+    )
+    .await
+}
+
+async fn run() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        None => run_projects(cli.run, Stage::All).await,
+        Some(Commands::Run(args)) => run_projects(args, Stage::All).await,
+        Some(Commands::Test(args)) => run_projects(args, Stage::Test).await,
+        Some(Commands::Bench(args)) => run_projects(args, Stage::Bench).await,
+        Some(Commands::Baseline) => {
+            Project::new(Path::new(ROOT_DIR).join("projects/tailcallhq"))?
+                .run_baseline()
+                .await
+        }
+        Some(Commands::Record) => {
+            Project::new(Path::new(ROOT_DIR).join("projects/tailcallhq"))?
+                .run_record()
+                .await
+        }
+        Some(Commands::Mocks) => generate_mocks(),
+        Some(Commands::Leaderboard) => update_leaderboard().await,
+        Some(Commands::Validate { project }) => validate(project.as_deref()),
+        Some(Commands::Compare(args)) => compare(args).await,
+    }
+}
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
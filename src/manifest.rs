@@ -0,0 +1,107 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+const MANIFEST_FILE: &str = "project.toml";
+
+/// Optional runner settings of the project from `projects/<name>/project.toml`
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectManifest {
+    /// Path of the GraphQL endpoint served by the project
+    pub path: String,
+    /// Script that starts the server
+    pub run: String,
+    /// Script that is executed to completion before the server starts
+    pub setup: Option<String>,
+    /// Seconds to wait until the server responds to the readiness query.
+    /// Falls back to `RUN_SCRIPT_RETRY_*` env variables when not set
+    pub startup_timeout: Option<u64>,
+    /// Query that should succeed once the server is ready
+    pub readiness_query: Option<String>,
+    /// Variables of the readiness query
+    pub readiness_variables: Map<String, Value>,
+    /// Additional environment variables for setup and run scripts
+    pub env: BTreeMap<String, String>,
+}
+
+impl Default for ProjectManifest {
+    fn default() -> Self {
+        Self {
+            path: "/graphql".to_owned(),
+            run: "run.sh".to_owned(),
+            setup: None,
+            startup_timeout: None,
+            readiness_query: None,
+            readiness_variables: Map::new(),
+            env: BTreeMap::new(),
+        }
+    }
+}
+
+impl ProjectManifest {
+    /// Reads the manifest from the project directory
+    /// or returns the defaults if there is no manifest
+    pub fn load(project_path: &Path) -> Result<Self> {
+        let manifest_path = project_path.join(MANIFEST_FILE);
+
+        if !manifest_path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&manifest_path)?;
+
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse `{}`", manifest_path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let manifest: Self = toml::from_str(content)?;
+
+        if !manifest.path.starts_with('/') {
+            bail!("`path` should start with `/`, got `{}`", manifest.path);
+        }
+
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectManifest;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = ProjectManifest::parse(
+            r#"
+path = "/api/graphql"
+setup = "setup.sh"
+startup_timeout = 300
+readiness_query = "query Post($id: Int!) { post(id: $id) { id } }"
+readiness_variables = { id = 1 }
+
+[env]
+NODE_ENV = "production"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.path, "/api/graphql");
+        assert_eq!(manifest.run, "run.sh");
+        assert_eq!(manifest.setup.as_deref(), Some("setup.sh"));
+        assert_eq!(manifest.startup_timeout, Some(300));
+        assert_eq!(
+            manifest.readiness_query.as_deref(),
+            Some("query Post($id: Int!) { post(id: $id) { id } }")
+        );
+        assert_eq!(manifest.readiness_variables["id"], 1);
+        assert_eq!(manifest.env["NODE_ENV"], "production");
+    }
+
+    #[test]
+    fn test_parse_manifest_invalid_path() {
+        assert!(ProjectManifest::parse(r#"path = "graphql""#).is_err());
+    }
+}
@@ -1,10 +1,13 @@
-use std::{fs, sync::{Arc, Mutex}};
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
 
 use anyhow::Result;
 use mock_json::{mock, registry, MockFn};
 use serde_json::{json, Value};
 
-const ROOT_DIR: &str = env!("CARGO_MANIFEST_DIR");
+use crate::ROOT_DIR;
 
 #[derive(Clone)]
 struct MockOrderedNumber {
@@ -44,7 +47,10 @@ fn generate(template: &Value, index: usize) -> Result<()> {
     Ok(())
 }
 
-fn main() -> Result<()> {
+/// Generates json files with mock data into `mocks` directory
+pub fn generate_mocks() -> Result<()> {
+    fs::create_dir_all(format!("{ROOT_DIR}/mocks"))?;
+
     let ordered_number_mock = MockOrderedNumber::new();
     registry("@OrderedNumber", ordered_number_mock.clone());
 
@@ -74,7 +80,7 @@ fn main() -> Result<()> {
 
     generate(&template, 1)?;
     ordered_number_mock.reset();
-	generate(&template, 2)?;
+    generate(&template, 2)?;
     ordered_number_mock.reset();
     generate(&template, 3)?;
 
@@ -0,0 +1,100 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::TcpListener,
+    sync::Mutex,
+};
+
+use anyhow::Result;
+
+/// Ports handed out during this run, never reused
+/// so parallel projects can't end up with the same port
+static TAKEN: Mutex<BTreeSet<u16>> = Mutex::new(BTreeSet::new());
+
+/// Ports of the servers started for a single project run.
+///
+/// Every spawned process receives all of them through the environment,
+/// so scripts can bind to their own port and reach the others
+#[derive(Debug)]
+pub struct Ports {
+    pub mock_api: u16,
+    pub reference: u16,
+    pub server: u16,
+    /// Listeners keeping the ports busy until the server is spawned
+    reserved: Mutex<HashMap<u16, TcpListener>>,
+}
+
+impl Ports {
+    /// Picks free ports provided by the OS
+    /// and keeps them reserved until [Ports::release]
+    pub fn allocate() -> Result<Self> {
+        let mut reserved = HashMap::new();
+        let mut ports = Vec::new();
+        let mut taken = TAKEN.lock().unwrap();
+
+        while ports.len() < 3 {
+            let listener = TcpListener::bind("127.0.0.1:0")?;
+            let port = listener.local_addr()?.port();
+
+            // the OS can hand out a port again once its listener is released,
+            // the listener stays open so the next bind gets another one
+            if taken.insert(port) {
+                ports.push(port);
+            }
+
+            reserved.insert(port, listener);
+        }
+
+        reserved.retain(|port, _| ports.contains(port));
+
+        Ok(Self {
+            mock_api: ports[0],
+            reference: ports[1],
+            server: ports[2],
+            reserved: Mutex::new(reserved),
+        })
+    }
+
+    /// Frees the port right before spawning the server that binds to it
+    pub fn release(&self, port: u16) {
+        self.reserved.lock().unwrap().remove(&port);
+    }
+
+    pub fn env(&self) -> [(&'static str, String); 3] {
+        [
+            ("MOCK_SERVER_PORT", self.mock_api.to_string()),
+            ("REFERENCE_SERVER_PORT", self.reference.to_string()),
+            ("SERVER_PORT", self.server.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::Ports;
+
+    #[test]
+    fn test_allocate() {
+        let first = Ports::allocate().unwrap();
+        let second = Ports::allocate().unwrap();
+        let mut ports = vec![
+            first.mock_api,
+            first.reference,
+            first.server,
+            second.mock_api,
+            second.reference,
+            second.server,
+        ];
+
+        ports.sort();
+        ports.dedup();
+        assert_eq!(ports.len(), 6);
+
+        assert!(TcpListener::bind(("127.0.0.1", first.server)).is_err());
+
+        first.release(first.server);
+
+        assert!(TcpListener::bind(("127.0.0.1", first.server)).is_ok());
+    }
+}
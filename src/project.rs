@@ -1,6 +1,9 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use easy_retry::EasyRetry;
+use reqwest::Method;
 use std::{
+    fmt,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -9,8 +12,14 @@ use tracing::{error, info, instrument};
 use crate::{
     benchmarks::run_benchmarks,
     command::{Command, CommandInstance},
-    graphql_tests::run_graphql_tests,
-    request::{REFERENCE_GRAPHQL_CLIENT, TESTED_GRAPHQL_CLIENT},
+    graphql_tests::{run_graphql_tests, Expected},
+    manifest::ProjectManifest,
+    ports::Ports,
+    regression::keep_previous_stats,
+    request::{GraphqlClient, GraphqlRequest, RestClient},
+    schema_check::schema_test_case,
+    snapshots::record_snapshots,
+    test_report::{TestReport, TestSuite},
     utils::env_default,
     ROOT_DIR,
 };
@@ -23,10 +32,52 @@ query {
 }
 ";
 
+/// Marks errors caused by the server process exiting unexpectedly
+#[derive(Debug)]
+pub struct ServerCrashed;
+
+impl fmt::Display for ServerCrashed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Server process exited unexpectedly")
+    }
+}
+
+/// Marks errors caused by the server not responding after startup
+#[derive(Debug)]
+pub struct ServerTimeout;
+
+impl fmt::Display for ServerTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Server did not become ready in time")
+    }
+}
+
+/// Part of the project run to execute
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+    /// Only the correctness tests
+    Test,
+    /// Only the benchmarks
+    Bench,
+    /// Tests, benchmarks and tests again
+    All,
+}
+
+impl Stage {
+    fn has_tests(self) -> bool {
+        matches!(self, Stage::Test | Stage::All)
+    }
+
+    pub fn has_benchmarks(self) -> bool {
+        matches!(self, Stage::Bench | Stage::All)
+    }
+}
+
 /// Runs tests and benchmarks for single project
 pub struct Project {
     path: PathBuf,
     name: String,
+    manifest: ProjectManifest,
 }
 
 impl Project {
@@ -37,42 +88,162 @@ impl Project {
             .to_string_lossy()
             .into_owned();
 
-        Ok(Project { path, name })
+        let manifest = ProjectManifest::load(&path)?;
+
+        Ok(Project {
+            path,
+            name,
+            manifest,
+        })
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    /// Run the tests and benchmarks
+    /// Checks the project layout without starting the server
+    pub fn validate(&self) -> Result<()> {
+        check_script(&self.path.join(&self.manifest.run))?;
+
+        if let Some(setup) = &self.manifest.setup {
+            check_script(&self.path.join(setup))?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the specified part of tests and benchmarks.
+    /// With `offline` the responses are compared with the recorded snapshots
+    /// instead of the reference server
     #[instrument(skip_all, fields(project = &self.name))]
-    pub async fn run_project(self) -> Result<()> {
+    pub async fn run_stage(self, stage: Stage, offline: bool) -> Result<()> {
         info!("Starting project: {}", &self.name);
 
-        let mock_server = self.run_mock_server().await?;
-        let reference_server = self.run_reference_server().await?;
-        let server = self.run_server().await?;
+        let ports = Ports::allocate()?;
+
+        info!(
+            "Using ports: mock api {}, reference {}, server {}",
+            ports.mock_api, ports.reference, ports.server
+        );
 
-        run_graphql_tests().await?;
-        run_benchmarks(&Path::new(ROOT_DIR).join(format!("results/{}", self.name()))).await?;
-        run_graphql_tests().await?;
+        let tested = GraphqlClient::new(ports.server, &self.manifest.path);
+        let reference = GraphqlClient::new(ports.reference, "/graphql");
+        let mock_api = RestClient::new(ports.mock_api);
+
+        let mock_server = self.run_mock_server(&ports).await?;
+        let reference_server = if stage.has_tests() && !offline {
+            Some(self.run_reference_server(&ports, &reference).await?)
+        } else {
+            None
+        };
+        let mut server = self.run_server(&ports, &tested).await?;
+
+        let expected = if offline {
+            Expected::Snapshots
+        } else {
+            Expected::Reference(&reference)
+        };
+
+        let result: Result<()> = async {
+            let mut report = TestReport::default();
+
+            if stage.has_tests() {
+                let mut suite = TestSuite::new("graphql-tests");
+
+                suite.push(schema_test_case(&tested).await);
+
+                self.run_tests(suite, &tested, &expected, &mock_api, &mut report)
+                    .await?;
+            }
+
+            if stage.has_benchmarks() {
+                keep_previous_stats(&self.results_path()).await?;
+                run_benchmarks(&self.results_path(), &tested, &server, &mock_api).await?;
+            }
+
+            // verify the server still works correctly after the load
+            if stage == Stage::All {
+                self.run_tests(
+                    TestSuite::new("graphql-tests-after-load"),
+                    &tested,
+                    &expected,
+                    &mock_api,
+                    &mut report,
+                )
+                .await?;
+            }
+
+            Ok(())
+        }
+        .await;
+
+        if let Err(error) = result {
+            return Err(if server.has_exited() {
+                error.context(ServerCrashed)
+            } else {
+                error
+            });
+        }
 
         info!("Kill the server process");
         server.kill().await?;
-        reference_server.kill().await?;
+
+        if let Some(reference_server) = reference_server {
+            reference_server.kill().await?;
+        }
+
         mock_server.kill().await?;
 
         Ok(())
     }
 
+    fn results_path(&self) -> PathBuf {
+        Path::new(ROOT_DIR).join("results").join(&self.name)
+    }
+
+    /// Runs the graphql tests and updates the test reports of the project
+    async fn run_tests(
+        &self,
+        suite: TestSuite,
+        tested: &GraphqlClient,
+        expected: &Expected<'_>,
+        mock_api: &RestClient,
+        report: &mut TestReport,
+    ) -> Result<()> {
+        let suite = run_graphql_tests(suite, tested, expected, mock_api).await?;
+        let (failures, tests) = (suite.failures, suite.tests);
+
+        report.suites.push(suite);
+        report.write(&self.results_path()).await?;
+
+        if failures > 0 {
+            bail!(
+                "{failures} of {tests} tests failed, see `{}`",
+                self.results_path().join("junit.xml").display()
+            );
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     pub async fn run_baseline(self) -> Result<()> {
         info!("Starting baseline project");
 
-        let mock_server = self.run_mock_server().await?;
-        let server = self.run_server().await?;
+        let ports = Ports::allocate()?;
+        let tested = GraphqlClient::new(ports.server, &self.manifest.path);
+        let mock_api = RestClient::new(ports.mock_api);
+
+        let mock_server = self.run_mock_server(&ports).await?;
+        let server = self.run_server(&ports, &tested).await?;
 
-        run_benchmarks(&Path::new(ROOT_DIR).join("reference/results")).await?;
+        run_benchmarks(
+            &Path::new(ROOT_DIR).join("reference/results"),
+            &tested,
+            &server,
+            &mock_api,
+        )
+        .await?;
 
         info!("Kill the server process");
         server.kill().await?;
@@ -81,8 +252,29 @@ impl Project {
         Ok(())
     }
 
+    /// Records responses of the reference server to `snapshots/`
     #[instrument(skip_all)]
-    async fn run_mock_server(&self) -> Result<CommandInstance> {
+    pub async fn run_record(self) -> Result<()> {
+        info!("Starting snapshots recording");
+
+        let ports = Ports::allocate()?;
+        let reference = GraphqlClient::new(ports.reference, "/graphql");
+        let mock_api = RestClient::new(ports.mock_api);
+
+        let mock_server = self.run_mock_server(&ports).await?;
+        let reference_server = self.run_reference_server(&ports, &reference).await?;
+
+        record_snapshots(&reference, &mock_api).await?;
+
+        info!("Kill the reference server process");
+        reference_server.kill().await?;
+        mock_server.kill().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn run_mock_server(&self, ports: &Ports) -> Result<CommandInstance> {
         info!("Starting mock server");
 
         let mut mock_path = PathBuf::from(ROOT_DIR);
@@ -94,34 +286,87 @@ impl Project {
             mock_path.join("debug/mock-api")
         };
         let mut command = Command::from_path(&mock_path)?;
+        command.envs(ports.env());
+        ports.release(ports.mock_api);
         let command = command.run()?;
 
-        // wait mock server to start
-        tokio::time::sleep(Duration::from_secs(1)).await;
+        // wait mock server to start, generating a big dataset
+        // (see `MOCK_SERVER_POSTS` and others) can take a while
+        let mock_api = RestClient::new(ports.mock_api);
+        EasyRetry::new_linear_async(1, env_default("MOCK_SERVER_STARTUP_ATTEMPTS", 60))
+            .run_async(move || {
+                let mock_api = mock_api.clone();
+
+                async move { mock_api.request(Method::GET, "stats").await }
+            })
+            .await
+            .context("Mock server is not responding")?;
 
         Ok(command)
     }
 
     #[instrument(skip_all)]
-    async fn run_server(&self) -> Result<CommandInstance> {
-        info!("Run run.sh");
-        let mut run_path = self.path.clone();
-        run_path.push("run.sh");
+    async fn run_setup(&self, ports: &Ports) -> Result<()> {
+        let Some(setup) = &self.manifest.setup else {
+            return Ok(());
+        };
+
+        info!("Run {setup}");
+
+        let mut command = Command::from_path(&self.path.join(setup))?;
+        command.envs(self.env());
+        command.envs(self.results_env()?);
+        command.envs(ports.env());
+        command
+            .run_and_wait()
+            .await
+            .with_context(|| format!("Setup script `{setup}` failed"))
+    }
+
+    #[instrument(skip_all)]
+    async fn run_server(&self, ports: &Ports, tested: &GraphqlClient) -> Result<CommandInstance> {
+        self.run_setup(ports).await?;
+
+        info!("Run {}", self.manifest.run);
+        let run_path = self.path.join(&self.manifest.run);
 
         let mut command = Command::from_path(&run_path)?;
-        let command = command.run()?;
+        // the runner owns the ports and the results dir,
+        // so they override the same keys in the manifest env
+        command.envs(self.env());
+        command.envs(self.results_env()?);
+        command.envs(ports.env());
+        ports.release(ports.server);
+        let mut command = command.run()?;
+
+        let retry = match self.manifest.startup_timeout {
+            Some(timeout) => EasyRetry::new_linear_async(1, timeout),
+            None => EasyRetry::new_linear_async(
+                env_default("RUN_SCRIPT_RETRY_TIMEOUT", 10),
+                env_default("RUN_SCRIPT_RETRY_ATTEMPTS", 100),
+            ),
+        };
 
-        let retry = EasyRetry::new_linear_async(
-            env_default("RUN_SCRIPT_RETRY_TIMEOUT", 10),
-            env_default("RUN_SCRIPT_RETRY_ATTEMPTS", 100),
-        );
+        let client = tested.clone();
+        let request = GraphqlRequest {
+            variables: self.manifest.readiness_variables.clone(),
+            ..GraphqlRequest::new(
+                self.manifest
+                    .readiness_query
+                    .as_deref()
+                    .unwrap_or(TEST_GRAPHQL_REQUEST),
+            )
+        };
 
         // wait until the server is ready for responses
-        retry
-            .run_async(|| async {
+        let readiness = retry.run_async(move || {
+            let client = client.clone();
+            let request = request.clone();
+
+            async move {
                 info!("Attempting to request the server");
 
-                let result = TESTED_GRAPHQL_CLIENT.request(TEST_GRAPHQL_REQUEST).await;
+                let result = client.request(&request).await;
 
                 if result.is_err() {
                     info!("Failed to resolve the response");
@@ -130,37 +375,99 @@ impl Project {
                 }
 
                 result
-            })
-            .await
-            .map_err(|_| {
-                error!(
-                    "Failed to request `http://localhost:8000/graphql` after multiple attempts.
-    Please, verify your setup"
-                );
+            }
+        });
+
+        let result = match self.manifest.startup_timeout {
+            Some(timeout) => tokio::time::timeout(Duration::from_secs(timeout), readiness)
+                .await
+                .unwrap_or_else(|_| Err(anyhow!("Startup timeout of {timeout}s exceeded"))),
+            None => readiness.await,
+        };
+
+        if let Err(error) = result {
+            error!(
+                "Failed to request `{}` after multiple attempts.
+    Please, verify your setup",
+                tested.url()
+            );
 
-                anyhow!("Server is not available")
-            })?;
+            let error = error.context("Server is not available");
+
+            return Err(if command.has_exited() {
+                error.context(ServerCrashed)
+            } else {
+                error.context(ServerTimeout)
+            });
+        }
 
         Ok(command)
     }
 
+    fn env(&self) -> impl Iterator<Item = (&str, String)> {
+        self.manifest
+            .env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+    }
+
+    /// Points the scripts to the results directory
+    /// to keep their generated files there
+    fn results_env(&self) -> Result<[(&'static str, String); 1]> {
+        let results_path = self.results_path();
+
+        std::fs::create_dir_all(&results_path)?;
+
+        Ok([("RESULTS_DIR", results_path.display().to_string())])
+    }
+
     #[instrument(skip_all)]
-    async fn run_reference_server(&self) -> Result<CommandInstance> {
+    async fn run_reference_server(
+        &self,
+        ports: &Ports,
+        reference: &GraphqlClient,
+    ) -> Result<CommandInstance> {
         info!("Start reference server");
         let mut run_path = PathBuf::from(ROOT_DIR);
         run_path.push("reference");
         run_path.push("run.sh");
 
         let mut command = Command::from_path(&run_path)?;
+        command.envs(ports.env());
+        command.envs(self.results_env()?);
+        ports.release(ports.reference);
         let command = command.run()?;
 
         let retry = EasyRetry::new_linear_async(1, 5);
+        let client = reference.clone();
 
         // wait until the server is ready for responses
         retry
-            .run_async(|| async { REFERENCE_GRAPHQL_CLIENT.request(TEST_GRAPHQL_REQUEST).await })
+            .run_async(move || {
+                let client = client.clone();
+
+                async move {
+                    client
+                        .request(&GraphqlRequest::new(TEST_GRAPHQL_REQUEST))
+                        .await
+                }
+            })
             .await?;
 
         Ok(command)
     }
 }
+
+fn check_script(path: &Path) -> Result<()> {
+    let metadata = std::fs::metadata(path)
+        .with_context(|| format!("Script `{}` not found", path.display()))?;
+
+    if metadata.permissions().mode() & 0o111 == 0 {
+        bail!(
+            "Script `{}` is not executable, run `chmod +x` on it",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
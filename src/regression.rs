@@ -0,0 +1,217 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::{bail, Result};
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::benchmarks::{AllStats, Stats};
+
+/// Allowed degradation in percents before the comparison fails
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    /// Drop of requests per second
+    pub rps: f64,
+    /// Growth of the p99 latency
+    pub latency: f64,
+}
+
+/// Change of a single benchmark between two runs
+#[derive(Debug)]
+struct BenchDelta {
+    name: String,
+    rps: Delta,
+    p50: Delta,
+    p99: Delta,
+    regressed: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct Delta {
+    previous: u64,
+    current: u64,
+}
+
+impl Delta {
+    fn new(previous: u64, current: u64) -> Self {
+        Self { previous, current }
+    }
+
+    /// Relative change in percents
+    fn change(&self) -> f64 {
+        if self.previous == 0 {
+            return 0.0;
+        }
+
+        100.0 * (self.current as f64 - self.previous as f64) / self.previous as f64
+    }
+}
+
+fn compare(current: &AllStats, previous: &AllStats, thresholds: Thresholds) -> Vec<BenchDelta> {
+    let mut deltas = Vec::new();
+
+    for (name, current) in current.iter() {
+        let Some(previous) = previous.get(name) else {
+            warn!("Benchmark `{name}` has no previous results");
+            continue;
+        };
+
+        deltas.push(bench_delta(name, current, previous, thresholds));
+    }
+
+    for name in previous.keys() {
+        if !current.contains_key(name) {
+            warn!("Benchmark `{name}` is missing from the current results");
+        }
+    }
+
+    deltas
+}
+
+fn bench_delta(
+    name: &str,
+    current: &Stats,
+    previous: &Stats,
+    thresholds: Thresholds,
+) -> BenchDelta {
+    let rps = Delta::new(previous.rps, current.rps);
+    let p50 = Delta::new(previous.latency.p50, current.latency.p50);
+    let p99 = Delta::new(previous.latency.p99, current.latency.p99);
+
+    let regressed = rps.change() < -thresholds.rps || p99.change() > thresholds.latency;
+
+    BenchDelta {
+        name: name.to_owned(),
+        rps,
+        p50,
+        p99,
+        regressed,
+    }
+}
+
+fn to_markdown(deltas: &[BenchDelta]) -> String {
+    let mut content =
+        "| Benchmark | RPS | p50, µs | p99, µs | Status |\n|---|---|---|---|---|\n".to_owned();
+
+    for delta in deltas {
+        let _ = writeln!(
+            content,
+            "|{}|{}|{}|{}|{}|",
+            delta.name,
+            format_delta(&delta.rps),
+            format_delta(&delta.p50),
+            format_delta(&delta.p99),
+            if delta.regressed { "regressed" } else { "ok" }
+        );
+    }
+
+    content
+}
+
+fn format_delta(delta: &Delta) -> String {
+    format!(
+        "{} → {} ({:+.1}%)",
+        delta.previous,
+        delta.current,
+        delta.change()
+    )
+}
+
+/// Copies `stats.json` of the last run of the project
+/// to `previous/` to compare the next run against
+pub async fn keep_previous_stats(results_path: &Path) -> Result<()> {
+    let json_path = results_path.join("stats.json");
+
+    if json_path.exists() {
+        let previous_path = results_path.join("previous");
+
+        fs::create_dir_all(&previous_path).await?;
+        fs::copy(&json_path, previous_path.join("stats.json")).await?;
+    }
+
+    Ok(())
+}
+
+/// Compares `stats.json` of two results directories
+/// and fails if any benchmark regressed more than the thresholds allow
+pub async fn compare_results(
+    current_path: &Path,
+    previous_path: &Path,
+    thresholds: Thresholds,
+) -> Result<()> {
+    let current = AllStats::load(current_path).await?;
+    let previous = AllStats::load(previous_path).await?;
+
+    let deltas = compare(&current, &previous, thresholds);
+
+    info!(
+        "Comparison of `{}` against `{}`:\n\n{}",
+        current_path.display(),
+        previous_path.display(),
+        to_markdown(&deltas)
+    );
+
+    let regressed: Vec<_> = deltas
+        .iter()
+        .filter(|delta| delta.regressed)
+        .map(|delta| delta.name.as_str())
+        .collect();
+
+    if !regressed.is_empty() {
+        bail!(
+            "Benchmarks regressed over the threshold of -{}% rps or +{}% p99 latency: {}",
+            thresholds.rps,
+            thresholds.latency,
+            regressed.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, to_markdown, Thresholds};
+    use crate::benchmarks::{AllStats, Stats};
+
+    fn stats(rps: u64, p50: u64, p99: u64) -> Stats {
+        let mut stats = Stats::default();
+
+        stats.rps = rps;
+        stats.latency.p50 = p50;
+        stats.latency.p99 = p99;
+
+        stats
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut previous = AllStats::default();
+        previous.insert("posts-nested".to_owned(), stats(1000, 800, 2000));
+        previous.insert("posts-title".to_owned(), stats(2000, 400, 1000));
+        previous.insert("removed".to_owned(), stats(100, 1, 1));
+
+        let mut current = AllStats::default();
+        current.insert("posts-nested".to_owned(), stats(1030, 790, 2100));
+        current.insert("posts-title".to_owned(), stats(1800, 450, 1050));
+        current.insert("added".to_owned(), stats(100, 1, 1));
+
+        let thresholds = Thresholds {
+            rps: 5.0,
+            latency: 10.0,
+        };
+        let deltas = compare(&current, &previous, thresholds);
+
+        assert_eq!(deltas.len(), 2);
+        assert!(!deltas[0].regressed);
+        assert!(deltas[1].regressed);
+        assert_eq!(deltas[1].rps.change(), -10.0);
+        assert_eq!(
+            to_markdown(&deltas),
+            "| Benchmark | RPS | p50, µs | p99, µs | Status |
+|---|---|---|---|---|
+|posts-nested|1000 → 1030 (+3.0%)|800 → 790 (-1.2%)|2000 → 2100 (+5.0%)|ok|
+|posts-title|2000 → 1800 (-10.0%)|400 → 450 (+12.5%)|1000 → 1050 (+5.0%)|regressed|
+"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::{fmt::Write, path::Path};
+
+use anyhow::{Error, Result};
+use serde::Serialize;
+use tokio::fs;
+
+use crate::project::{ServerCrashed, ServerTimeout};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectStatus {
+    Pass,
+    Fail,
+    Crash,
+    Timeout,
+}
+
+/// Outcome of a single project run
+#[derive(Serialize, Debug)]
+pub struct ProjectReport {
+    pub name: String,
+    pub status: ProjectStatus,
+    /// Error chain from the outermost context to the root cause
+    pub errors: Vec<String>,
+}
+
+impl ProjectReport {
+    pub fn new(name: String, result: &Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                name,
+                status: ProjectStatus::Pass,
+                errors: Vec::new(),
+            },
+            Err(error) => Self {
+                name,
+                status: classify(error),
+                errors: error.chain().map(|cause| cause.to_string()).collect(),
+            },
+        }
+    }
+}
+
+fn classify(error: &Error) -> ProjectStatus {
+    if error.downcast_ref::<ServerCrashed>().is_some() {
+        ProjectStatus::Crash
+    } else if error.downcast_ref::<ServerTimeout>().is_some() {
+        ProjectStatus::Timeout
+    } else {
+        ProjectStatus::Fail
+    }
+}
+
+/// Statuses of all projects of the run
+#[derive(Serialize, Debug, Default)]
+pub struct Summary {
+    pub projects: Vec<ProjectReport>,
+}
+
+impl Summary {
+    pub fn failed(&self) -> usize {
+        self.projects
+            .iter()
+            .filter(|project| project.status != ProjectStatus::Pass)
+            .count()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut content = "| Project | Status | Error |\n|---|---|---|\n".to_owned();
+
+        for project in &self.projects {
+            let _ = writeln!(
+                content,
+                "|{}|{:?}|{}|",
+                project.name,
+                project.status,
+                project
+                    .errors
+                    .join(": ")
+                    .replace('|', "\\|")
+                    .replace('\n', " ")
+            );
+        }
+
+        content
+    }
+
+    /// Writes `summary.json` and `summary.md` into the directory
+    pub async fn write(&self, output_path: &Path) -> Result<()> {
+        fs::create_dir_all(output_path).await?;
+        fs::write(
+            output_path.join("summary.json"),
+            serde_json::to_string_pretty(self)?,
+        )
+        .await?;
+        fs::write(output_path.join("summary.md"), self.to_markdown()).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::{ProjectReport, ProjectStatus, Summary};
+    use crate::project::{ServerCrashed, ServerTimeout};
+
+    #[test]
+    fn test_project_status() {
+        let pass = ProjectReport::new("pass".to_owned(), &Ok(()));
+        let fail = ProjectReport::new(
+            "fail".to_owned(),
+            &Err(anyhow!("Actual response is not equal to expected")),
+        );
+        let crash = ProjectReport::new(
+            "crash".to_owned(),
+            &Err(anyhow!("Connection refused").context(ServerCrashed)),
+        );
+        let timeout = ProjectReport::new(
+            "timeout".to_owned(),
+            &Err(anyhow!("Server is not available").context(ServerTimeout)),
+        );
+
+        assert_eq!(pass.status, ProjectStatus::Pass);
+        assert_eq!(fail.status, ProjectStatus::Fail);
+        assert_eq!(crash.status, ProjectStatus::Crash);
+        assert_eq!(
+            crash.errors,
+            ["Server process exited unexpectedly", "Connection refused"]
+        );
+        assert_eq!(timeout.status, ProjectStatus::Timeout);
+
+        let summary = Summary {
+            projects: vec![pass, fail, crash, timeout],
+        };
+
+        assert_eq!(summary.failed(), 3);
+        assert_eq!(
+            summary.to_markdown(),
+            "| Project | Status | Error |
+|---|---|---|
+|pass|Pass||
+|fail|Fail|Actual response is not equal to expected|
+|crash|Crash|Server process exited unexpectedly: Connection refused|
+|timeout|Timeout|Server did not become ready in time: Server is not available|
+"
+        );
+    }
+}
@@ -1,44 +1,64 @@
 use anyhow::Result;
 use reqwest::Method;
-use serde_json::json;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
+/// Body of the GraphQL request
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphqlRequest {
+    pub query: String,
+    /// Operation to execute if the query contains multiple operations
+    pub operation_name: Option<String>,
+    pub variables: Map<String, Value>,
+}
+
+impl GraphqlRequest {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct GraphqlClient {
-    api: &'static str,
+    api: String,
 }
 
+#[derive(Clone)]
 pub struct RestClient {
-    api: &'static str,
+    api: String,
 }
 
-pub const TESTED_GRAPHQL_CLIENT: GraphqlClient = GraphqlClient {
-    api: "http://localhost:8000/graphql",
-};
-
-pub const REFERENCE_GRAPHQL_CLIENT: GraphqlClient = GraphqlClient {
-    api: "http://localhost:8089/graphql",
-};
+impl GraphqlClient {
+    pub fn new(port: u16, path: &str) -> Self {
+        Self {
+            api: format!("http://localhost:{port}{path}"),
+        }
+    }
 
-pub const MOCK_API_CLIENT: RestClient = RestClient {
-    api: "http://localhost:3000",
-};
+    pub fn url(&self) -> &str {
+        &self.api
+    }
 
-impl GraphqlClient {
-    pub async fn request(&self, query: &str) -> Result<serde_json::Value> {
+    pub async fn request(&self, request: &GraphqlRequest) -> Result<serde_json::Value> {
         let client = reqwest::Client::new();
 
-        let value = json!({
-            "operationName": null,
-            "variables": {},
-            "query": query
-        });
-
-        let response = client.post(self.api).json(&value).send().await?;
+        let response = client.post(&self.api).json(request).send().await?;
 
         Ok(response.json().await?)
     }
 }
 
 impl RestClient {
+    pub fn new(port: u16) -> Self {
+        Self {
+            api: format!("http://localhost:{port}"),
+        }
+    }
+
     pub async fn request(&self, method: Method, path: &str) -> Result<serde_json::Value> {
         let client = reqwest::Client::new();
 
@@ -49,4 +69,23 @@ impl RestClient {
 
         Ok(response.json().await?)
     }
+
+    /// Sends the json body and fails on unsuccessful status
+    pub async fn request_json<T: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &T,
+    ) -> Result<serde_json::Value> {
+        let client = reqwest::Client::new();
+
+        let response = client
+            .request(method, format!("{}/{}", self.api, path))
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(response.json().await?)
+    }
 }
@@ -0,0 +1,213 @@
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Single reading of all processes inside the group
+struct Sample {
+    at: Instant,
+    /// Sum of user and system time in clock ticks
+    cpu_ticks: u64,
+    /// Resident set size in pages
+    rss_pages: u64,
+}
+
+/// CPU and memory usage of a process group during some interval
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ResourceUsage {
+    /// Average CPU usage in percents of a single core
+    pub cpu_avg: f64,
+    /// Highest CPU usage between two samples in percents of a single core
+    pub cpu_peak: f64,
+    /// Average resident memory in bytes
+    pub rss_avg: u64,
+    /// Highest resident memory in bytes
+    pub rss_peak: u64,
+}
+
+/// Periodically samples CPU time and RSS of the process group from `/proc`
+pub struct ResourceSampler {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<Vec<Sample>>,
+}
+
+impl ResourceSampler {
+    pub fn start(pgid: u32) -> Self {
+        let (stop, mut stopped) = oneshot::channel();
+
+        let handle = tokio::spawn(async move {
+            let mut samples = Vec::new();
+            let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = &mut stopped => break,
+                }
+
+                match sample_group(pgid) {
+                    Some(sample) => samples.push(sample),
+                    // `/proc` is not available, e.g. not on linux
+                    None => break,
+                }
+            }
+
+            samples.extend(sample_group(pgid));
+
+            samples
+        });
+
+        Self { stop, handle }
+    }
+
+    /// Stops sampling and aggregates the collected samples.
+    /// Returns `None` if there is not enough data
+    pub async fn finish(self) -> Option<ResourceUsage> {
+        let _ = self.stop.send(());
+        let samples = self.handle.await.ok()?;
+
+        aggregate(&samples, clock_ticks()?, page_size()?)
+    }
+}
+
+fn aggregate(samples: &[Sample], clock_ticks: f64, page_size: u64) -> Option<ResourceUsage> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let cpu_percent = |from: &Sample, to: &Sample| {
+        let wall = to.at.duration_since(from.at).as_secs_f64();
+        let cpu = to.cpu_ticks.saturating_sub(from.cpu_ticks) as f64 / clock_ticks;
+
+        if wall > 0.0 {
+            100.0 * cpu / wall
+        } else {
+            0.0
+        }
+    };
+
+    let cpu_peak = samples
+        .windows(2)
+        .map(|pair| cpu_percent(&pair[0], &pair[1]))
+        .fold(0.0, f64::max);
+    let rss_peak = samples.iter().map(|s| s.rss_pages).max()? * page_size;
+    let rss_avg =
+        samples.iter().map(|s| s.rss_pages).sum::<u64>() * page_size / samples.len() as u64;
+
+    Some(ResourceUsage {
+        cpu_avg: cpu_percent(first, last),
+        cpu_peak,
+        rss_avg,
+        rss_peak,
+    })
+}
+
+/// Sums usage of every process whose process group is `pgid`
+fn sample_group(pgid: u32) -> Option<Sample> {
+    let mut sample = Sample {
+        at: Instant::now(),
+        cpu_ticks: 0,
+        rss_pages: 0,
+    };
+
+    for entry in fs::read_dir("/proc").ok()?.flatten() {
+        let is_pid = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()));
+
+        if !is_pid {
+            continue;
+        }
+
+        // the process could exit in the meantime
+        let Ok(stat) = fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+
+        if let Some((group, cpu_ticks, rss_pages)) = parse_stat(&stat) {
+            if group == pgid {
+                sample.cpu_ticks += cpu_ticks;
+                sample.rss_pages += rss_pages;
+            }
+        }
+    }
+
+    Some(sample)
+}
+
+/// Extracts process group, utime + stime and rss from `/proc/<pid>/stat`
+fn parse_stat(stat: &str) -> Option<(u32, u64, u64)> {
+    // skip `pid (comm)` since comm may contain spaces
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+
+    // fields are numbered from `state` which is the 3rd field in proc(5)
+    let pgrp = fields.get(2)?.parse().ok()?;
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let rss: i64 = fields.get(21)?.parse().ok()?;
+
+    Some((pgrp, utime + stime, rss.max(0) as u64))
+}
+
+fn clock_ticks() -> Option<f64> {
+    // SAFETY: sysconf has no preconditions
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+
+    (ticks > 0).then_some(ticks as f64)
+}
+
+fn page_size() -> Option<u64> {
+    // SAFETY: sysconf has no preconditions
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+    (size > 0).then_some(size as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{aggregate, parse_stat, Sample};
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = "1234 (tail call) S 1 1200 1200 0 -1 4194560 100 0 0 0 25 15 0 0 20 0 4 0 100 1000000 512 18446744073709551615";
+
+        assert_eq!(parse_stat(stat), Some((1200, 40, 512)));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let start = Instant::now();
+        let samples = [
+            Sample {
+                at: start,
+                cpu_ticks: 0,
+                rss_pages: 10,
+            },
+            Sample {
+                at: start + Duration::from_secs(1),
+                cpu_ticks: 50,
+                rss_pages: 20,
+            },
+            Sample {
+                at: start + Duration::from_secs(2),
+                cpu_ticks: 200,
+                rss_pages: 30,
+            },
+        ];
+
+        let usage = aggregate(&samples, 100.0, 4096).unwrap();
+
+        assert_eq!(usage.cpu_avg, 100.0);
+        assert_eq!(usage.cpu_peak, 150.0);
+        assert_eq!(usage.rss_avg, 20 * 4096);
+        assert_eq!(usage.rss_peak, 30 * 4096);
+    }
+}
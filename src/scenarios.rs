@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::LazyLock};
+
+use anyhow::{bail, Context, Result};
+use graphql_parser::query::{self, Definition, OperationDefinition};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{Map, Value};
+
+use crate::{compare::CompareOptions, request::GraphqlRequest, ROOT_DIR};
+
+/// Comparison options for all tests
+const COMPARE_FILE: &str = "compare.json";
+
+/// Query from `tests/<name>.graphql` that is used both
+/// for the correctness test and for the benchmark
+pub(crate) struct Scenario {
+    pub(crate) name: String,
+    /// Requests executed one after another, every response is compared
+    pub(crate) steps: Vec<GraphqlRequest>,
+    /// Set when the query contains a mutation,
+    /// the mock data is restored before and after such scenarios
+    pub(crate) mutates: bool,
+    /// Faults of the mock server while the scenario runs
+    pub(crate) faults: Option<Value>,
+    pub(crate) compare: CompareOptions,
+    pub(crate) bench: BenchMeta,
+}
+
+impl Scenario {
+    /// Request of the benchmark, scenarios with several steps are never benchmarked
+    pub(crate) fn request(&self) -> &GraphqlRequest {
+        &self.steps[0]
+    }
+}
+
+/// Optional settings of the scenario from `tests/<name>.json`
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+struct ScenarioMeta {
+    /// Operation to execute if the file contains multiple operations
+    operation_name: Option<String>,
+    variables: Map<String, Value>,
+    /// Operations to execute one after another instead of a single one
+    steps: Vec<StepMeta>,
+    /// Faults injected into the mock server while the scenario runs,
+    /// in the format of `faults` of `PATCH /admin` of the mock server
+    faults: Option<Value>,
+    /// Overrides options from `tests/compare.json`
+    compare: CompareOptions,
+    bench: BenchMeta,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+struct StepMeta {
+    operation_name: Option<String>,
+    variables: Map<String, Value>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct BenchMeta {
+    /// Do not benchmark the scenario, only test it
+    pub(crate) skip: bool,
+    /// Duration of a single run in seconds, overrides `BENCH_DURATION`
+    pub(crate) duration: Option<u64>,
+    /// Number of concurrent connections, overrides `BENCH_CONNECTIONS`
+    pub(crate) connections: Option<usize>,
+    /// Additional headers sent with every request
+    pub(crate) headers: BTreeMap<String, String>,
+}
+
+pub(crate) static SCENARIOS: LazyLock<Result<Vec<Scenario>>> = LazyLock::new(|| {
+    let tests_path = Path::new(ROOT_DIR).join("tests");
+    let mut scenarios = Vec::new();
+
+    let compare = read_json_or_default::<CompareOptions>(&tests_path.join(COMPARE_FILE))?;
+
+    for entry in fs::read_dir(tests_path)? {
+        let path = entry?.path();
+
+        if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
+            if ext == "graphql" {
+                scenarios.push(load_scenario(&path, &compare)?);
+            }
+        }
+    }
+
+    scenarios.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(scenarios)
+});
+
+fn load_scenario(path: &Path, compare: &CompareOptions) -> Result<Scenario> {
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy())
+        .context("expected the name")?;
+
+    let meta: ScenarioMeta = read_json_or_default(&path.with_extension("json"))?;
+    let query = fs::read_to_string(path)?;
+
+    let steps = if meta.steps.is_empty() {
+        vec![StepMeta {
+            operation_name: meta.operation_name,
+            variables: meta.variables,
+        }]
+    } else if meta.operation_name.is_some() || !meta.variables.is_empty() {
+        bail!("Scenario `{name}` has steps, set `operationName` and `variables` of every step instead");
+    } else {
+        meta.steps
+    };
+
+    let mutates = has_mutation(&query);
+
+    if !meta.bench.skip && (steps.len() > 1 || mutates || meta.faults.is_some()) {
+        bail!("Scenario `{name}` changes the data, injects faults or has several steps and can't be benchmarked, set `bench.skip`");
+    }
+
+    let steps: Vec<_> = steps
+        .into_iter()
+        .map(|step| GraphqlRequest {
+            query: query.clone(),
+            operation_name: step.operation_name,
+            variables: step.variables,
+        })
+        .collect();
+
+    Ok(Scenario {
+        name: name.to_string(),
+        steps,
+        mutates,
+        faults: meta.faults,
+        compare: meta.compare.or(compare),
+        bench: meta.bench,
+    })
+}
+
+/// Invalid queries are expected to fail before changing anything
+fn has_mutation(query: &str) -> bool {
+    let Ok(document) = query::parse_query::<String>(query) else {
+        return false;
+    };
+
+    document.definitions.iter().any(|definition| {
+        matches!(
+            definition,
+            Definition::Operation(OperationDefinition::Mutation(_))
+        )
+    })
+}
+
+fn read_json_or_default<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+
+    serde_json::from_str(&fs::read_to_string(path)?)
+        .with_context(|| format!("Failed to parse `{}`", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_mutation, ScenarioMeta};
+
+    #[test]
+    fn test_parse_meta() {
+        let meta: ScenarioMeta = serde_json::from_str(
+            r#"{
+                "operationName": "UserById",
+                "variables": { "id": 1 },
+                "bench": {
+                    "duration": 10,
+                    "connections": 50,
+                    "headers": { "Authorization": "Bearer token" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(meta.operation_name.as_deref(), Some("UserById"));
+        assert_eq!(meta.variables["id"], 1);
+        assert!(!meta.bench.skip);
+        assert_eq!(meta.bench.duration, Some(10));
+        assert_eq!(meta.bench.connections, Some(50));
+        assert_eq!(meta.bench.headers["Authorization"], "Bearer token");
+
+        let meta: ScenarioMeta = serde_json::from_str(r#"{ "bench": { "skip": true } }"#).unwrap();
+
+        assert!(meta.bench.skip);
+        assert!(serde_json::from_str::<ScenarioMeta>(r#"{ "bench": { "time": 1 } }"#).is_err());
+    }
+
+    #[test]
+    fn test_has_mutation() {
+        assert!(!has_mutation("query Posts { posts { id } }"));
+        assert!(has_mutation(
+            "query Post { post(id: 1) { id } } mutation Delete { deletePost(id: 1) { id } }"
+        ));
+        assert!(!has_mutation("mutation {"));
+    }
+}
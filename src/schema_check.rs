@@ -0,0 +1,446 @@
+use std::{collections::BTreeMap, time::Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use graphql_parser::schema::{self, Definition, TypeDefinition};
+use serde::Deserialize;
+use tracing::{error, info, instrument};
+
+use crate::{
+    request::{GraphqlClient, GraphqlRequest},
+    test_report::{TestCase, TestFailure},
+    ROOT_DIR,
+};
+
+const INTROSPECTION_QUERY: &str = "
+query {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    types {
+      name
+      kind
+      fields { name args { name type { ...TypeRef } } type { ...TypeRef } }
+      inputFields { name type { ...TypeRef } }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+}
+";
+
+/// Fields of object and input types with their arguments,
+/// types are rendered like in SDL, e.g. `[Post]` or `Int!`
+#[derive(Debug, Default)]
+struct SchemaShape {
+    query_type: String,
+    mutation_type: Option<String>,
+    types: BTreeMap<String, BTreeMap<String, FieldShape>>,
+}
+
+#[derive(Debug, Default)]
+struct FieldShape {
+    ty: String,
+    args: BTreeMap<String, String>,
+}
+
+impl SchemaShape {
+    fn from_sdl(sdl: &str) -> Result<Self> {
+        let document = schema::parse_schema::<String>(sdl)?;
+        let mut shape = SchemaShape {
+            query_type: "Query".to_owned(),
+            ..Default::default()
+        };
+
+        for definition in document.definitions {
+            match definition {
+                Definition::SchemaDefinition(schema) => {
+                    if let Some(query) = schema.query {
+                        shape.query_type = query;
+                    }
+
+                    shape.mutation_type = schema.mutation;
+                }
+                Definition::TypeDefinition(TypeDefinition::Object(object)) => {
+                    let fields = object
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            let args = field
+                                .arguments
+                                .iter()
+                                .map(|arg| (arg.name.clone(), render_sdl_type(&arg.value_type)))
+                                .collect();
+
+                            (
+                                field.name.clone(),
+                                FieldShape {
+                                    ty: render_sdl_type(&field.field_type),
+                                    args,
+                                },
+                            )
+                        })
+                        .collect();
+
+                    shape.types.insert(object.name, fields);
+                }
+                Definition::TypeDefinition(TypeDefinition::InputObject(input)) => {
+                    let fields = input
+                        .fields
+                        .iter()
+                        .map(|field| {
+                            (
+                                field.name.clone(),
+                                FieldShape {
+                                    ty: render_sdl_type(&field.value_type),
+                                    args: BTreeMap::new(),
+                                },
+                            )
+                        })
+                        .collect();
+
+                    shape.types.insert(input.name, fields);
+                }
+                _ => {}
+            }
+        }
+
+        if shape.mutation_type.is_none() && shape.types.contains_key("Mutation") {
+            shape.mutation_type = Some("Mutation".to_owned());
+        }
+
+        Ok(shape)
+    }
+
+    fn from_introspection(schema: IntrospectionSchema) -> Self {
+        let mut shape = SchemaShape {
+            query_type: schema.query_type.name,
+            mutation_type: schema.mutation_type.map(|ty| ty.name),
+            ..Default::default()
+        };
+
+        for ty in schema.types {
+            // skip built-in types like `__Schema`
+            if ty.name.starts_with("__") {
+                continue;
+            }
+
+            let fields = match (ty.kind.as_str(), ty.fields, ty.input_fields) {
+                ("OBJECT", Some(fields), _) => fields
+                    .into_iter()
+                    .map(|field| {
+                        let args = field
+                            .args
+                            .into_iter()
+                            .map(|arg| (arg.name, arg.ty.render()))
+                            .collect();
+
+                        (
+                            field.name,
+                            FieldShape {
+                                ty: field.ty.render(),
+                                args,
+                            },
+                        )
+                    })
+                    .collect(),
+                ("INPUT_OBJECT", _, Some(fields)) => fields
+                    .into_iter()
+                    .map(|field| {
+                        (
+                            field.name,
+                            FieldShape {
+                                ty: field.ty.render(),
+                                args: BTreeMap::new(),
+                            },
+                        )
+                    })
+                    .collect(),
+                _ => continue,
+            };
+
+            shape.types.insert(ty.name, fields);
+        }
+
+        shape
+    }
+
+    /// Type that has the same role in the actual schema,
+    /// root types may have different names
+    fn actual_name<'a>(&'a self, expected: &'a SchemaShape, name: &'a str) -> Option<&'a str> {
+        if name == expected.query_type {
+            Some(&self.query_type)
+        } else if Some(name) == expected.mutation_type.as_deref() {
+            self.mutation_type.as_deref()
+        } else {
+            Some(name)
+        }
+    }
+}
+
+fn render_sdl_type(ty: &schema::Type<String>) -> String {
+    match ty {
+        schema::Type::NamedType(name) => name.clone(),
+        schema::Type::ListType(inner) => format!("[{}]", render_sdl_type(inner)),
+        schema::Type::NonNullType(inner) => format!("{}!", render_sdl_type(inner)),
+    }
+}
+
+#[derive(Deserialize)]
+struct IntrospectionResponse {
+    data: Option<IntrospectionData>,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionData {
+    #[serde(rename = "__schema")]
+    schema: IntrospectionSchema,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectionSchema {
+    query_type: NamedType,
+    mutation_type: Option<NamedType>,
+    types: Vec<IntrospectionType>,
+}
+
+#[derive(Deserialize)]
+struct NamedType {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectionType {
+    name: String,
+    kind: String,
+    fields: Option<Vec<IntrospectionField>>,
+    input_fields: Option<Vec<IntrospectionField>>,
+}
+
+#[derive(Deserialize)]
+struct IntrospectionField {
+    name: String,
+    #[serde(default)]
+    args: Vec<IntrospectionField>,
+    #[serde(rename = "type")]
+    ty: TypeRef,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TypeRef {
+    kind: String,
+    name: Option<String>,
+    of_type: Option<Box<TypeRef>>,
+}
+
+impl TypeRef {
+    fn render(&self) -> String {
+        let inner = || {
+            self.of_type
+                .as_ref()
+                .map(|ty| ty.render())
+                .unwrap_or_default()
+        };
+
+        match self.kind.as_str() {
+            "NON_NULL" => format!("{}!", inner()),
+            "LIST" => format!("[{}]", inner()),
+            _ => self.name.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Lists the differences of the actual schema from the expected one.
+/// Extra types and fields are allowed except the root fields
+fn diff_schemas(expected: &SchemaShape, actual: &SchemaShape) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for (type_name, expected_fields) in &expected.types {
+        let actual_fields = actual
+            .actual_name(expected, type_name)
+            .and_then(|name| actual.types.get(name));
+
+        let Some(actual_fields) = actual_fields else {
+            problems.push(format!("Type `{type_name}` is missing"));
+            continue;
+        };
+
+        for (field_name, expected_field) in expected_fields {
+            let Some(actual_field) = actual_fields.get(field_name) else {
+                problems.push(format!("Field `{type_name}.{field_name}` is missing"));
+                continue;
+            };
+
+            if actual_field.ty != expected_field.ty {
+                problems.push(format!(
+                    "Field `{type_name}.{field_name}` has type `{}`, expected `{}`",
+                    actual_field.ty, expected_field.ty
+                ));
+            }
+
+            for (arg_name, expected_arg) in &expected_field.args {
+                match actual_field.args.get(arg_name) {
+                    None => problems.push(format!(
+                        "Argument `{type_name}.{field_name}({arg_name}:)` is missing"
+                    )),
+                    Some(actual_arg) if actual_arg != expected_arg => problems.push(format!(
+                        "Argument `{type_name}.{field_name}({arg_name}:)` has type `{actual_arg}`, expected `{expected_arg}`"
+                    )),
+                    _ => {}
+                }
+            }
+        }
+
+        let is_root = type_name == &expected.query_type
+            || Some(type_name.as_str()) == expected.mutation_type.as_deref();
+
+        if is_root {
+            for field_name in actual_fields.keys() {
+                if !expected_fields.contains_key(field_name) {
+                    problems.push(format!(
+                        "Root field `{type_name}.{field_name}` is not in the schema"
+                    ));
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+/// Runs the schema check as the `schema` test case,
+/// so a mismatch is reported together with the graphql tests
+pub(crate) async fn schema_test_case(tested: &GraphqlClient) -> TestCase {
+    let started = Instant::now();
+    let failure = check_schema(tested).await.err().map(|error| {
+        error!("Test `schema` failed: {error:#}");
+
+        TestFailure::new(format!("{error:#}"), None)
+    });
+
+    TestCase {
+        name: "schema".to_owned(),
+        iteration: 1,
+        seed: None,
+        time: started.elapsed().as_secs_f64(),
+        failure,
+    }
+}
+
+/// Compares the schema of the tested server received with introspection
+/// with `schema.graphql`
+#[instrument(skip_all)]
+async fn check_schema(tested: &GraphqlClient) -> Result<()> {
+    info!("Check schema conformance");
+
+    let sdl = std::fs::read_to_string(format!("{ROOT_DIR}/schema.graphql"))?;
+    let expected = SchemaShape::from_sdl(&sdl).context("Failed to parse `schema.graphql`")?;
+
+    let response = tested
+        .request(&GraphqlRequest::new(INTROSPECTION_QUERY))
+        .await?;
+    let response: IntrospectionResponse =
+        serde_json::from_value(response).context("Failed to parse the introspection response")?;
+    let schema = response
+        .data
+        .ok_or(anyhow!("Introspection query returned no data"))?
+        .schema;
+
+    let problems = diff_schemas(&expected, &SchemaShape::from_introspection(schema));
+
+    if !problems.is_empty() {
+        for problem in &problems {
+            error!("{problem}");
+        }
+
+        bail!(
+            "Schema of the server doesn't match `schema.graphql`: {}",
+            problems.join(", ")
+        );
+    }
+
+    info!("Schema of the server matches `schema.graphql`");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_schemas, IntrospectionSchema, SchemaShape};
+
+    #[test]
+    fn test_diff_schemas() {
+        let expected = SchemaShape::from_sdl(
+            "
+schema { query: Query }
+
+type Query {
+  posts: [Post]
+  post(id: Int!): Post
+}
+
+type Post {
+  id: Int
+  userId: Int!
+  title: String
+}
+",
+        )
+        .unwrap();
+
+        let actual: IntrospectionSchema = serde_json::from_value(serde_json::json!({
+            "queryType": { "name": "RootQuery" },
+            "mutationType": null,
+            "types": [
+                {
+                    "name": "RootQuery",
+                    "kind": "OBJECT",
+                    "fields": [
+                        {
+                            "name": "posts",
+                            "args": [],
+                            "type": { "kind": "LIST", "name": null, "ofType": { "kind": "OBJECT", "name": "Post" } }
+                        },
+                        {
+                            "name": "post",
+                            "args": [{ "name": "id", "type": { "kind": "SCALAR", "name": "Int" } }],
+                            "type": { "kind": "OBJECT", "name": "Post" }
+                        },
+                        {
+                            "name": "comments",
+                            "args": [],
+                            "type": { "kind": "SCALAR", "name": "String" }
+                        }
+                    ]
+                },
+                {
+                    "name": "Post",
+                    "kind": "OBJECT",
+                    "fields": [
+                        { "name": "id", "args": [], "type": { "kind": "SCALAR", "name": "Int" } },
+                        { "name": "userId", "args": [], "type": { "kind": "SCALAR", "name": "Int" } },
+                        { "name": "body", "args": [], "type": { "kind": "SCALAR", "name": "String" } }
+                    ]
+                },
+                { "name": "__Schema", "kind": "OBJECT", "fields": [] }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            diff_schemas(&expected, &SchemaShape::from_introspection(actual)),
+            [
+                "Field `Post.title` is missing",
+                "Field `Post.userId` has type `Int`, expected `Int!`",
+                "Argument `Query.post(id:)` has type `Int`, expected `Int!`",
+                "Root field `Query.comments` is not in the schema",
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::fs;
+use tracing::{info, instrument};
+
+use crate::{
+    graphql_tests::{base_seed, replace_faults, reset_mock_data, NUMBER_OF_TESTS},
+    request::{GraphqlClient, GraphqlRequest, RestClient},
+    scenarios::{Scenario, SCENARIOS},
+    ROOT_DIR,
+};
+
+const DATASET_FILE: &str = "dataset.json";
+
+/// Response of the reference server to a step of the scenario
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    /// Request the response was recorded for,
+    /// used to detect outdated snapshots
+    request: GraphqlRequest,
+    response: Value,
+}
+
+fn snapshots_path() -> PathBuf {
+    Path::new(ROOT_DIR).join("snapshots")
+}
+
+fn iteration_path(iteration: usize) -> PathBuf {
+    snapshots_path().join(format!("iteration-{iteration}"))
+}
+
+/// Stores the mock dataset and reference responses
+/// for every test iteration into `snapshots/`
+#[instrument(skip_all)]
+pub async fn record_snapshots(reference: &GraphqlClient, mock_api: &RestClient) -> Result<()> {
+    info!("Record snapshots");
+
+    let scenarios = SCENARIOS
+        .as_ref()
+        .map_err(|e| anyhow!("Failed to resolve tests due to error: {e:#}"))?;
+    let snapshots_path = snapshots_path();
+
+    if snapshots_path.exists() {
+        fs::remove_dir_all(&snapshots_path).await?;
+    }
+
+    let base_seed = base_seed();
+
+    for i in 1..NUMBER_OF_TESTS {
+        let seed = base_seed + i as u64 - 1;
+
+        info!("Record iteration: {i}, mock data seed: {seed}");
+
+        let iteration_path = iteration_path(i);
+        fs::create_dir_all(&iteration_path).await?;
+
+        reset_mock_data(mock_api, seed).await?;
+
+        let dataset = mock_api.request(Method::GET, "database").await?;
+        write_json(&iteration_path.join(DATASET_FILE), &dataset).await?;
+
+        for scenario in scenarios {
+            let mut snapshots = Vec::with_capacity(scenario.steps.len());
+
+            let previous_faults = match &scenario.faults {
+                Some(faults) => Some(replace_faults(mock_api, faults).await?),
+                None => None,
+            };
+
+            for request in &scenario.steps {
+                snapshots.push(Snapshot {
+                    request: request.clone(),
+                    response: reference.request(request).await?,
+                });
+            }
+
+            if let Some(previous) = previous_faults {
+                replace_faults(mock_api, &previous).await?;
+            }
+
+            // the next scenarios expect the initial data
+            if scenario.mutates {
+                reset_mock_data(mock_api, seed).await?;
+            }
+
+            write_json(
+                &iteration_path.join(format!("{}.json", scenario.name)),
+                &snapshots,
+            )
+            .await?;
+        }
+    }
+
+    info!("Snapshots are written to `{}`", snapshots_path.display());
+
+    Ok(())
+}
+
+/// Loads the recorded dataset of the iteration into the mock server
+pub(crate) async fn restore_dataset(iteration: usize, mock_api: &RestClient) -> Result<()> {
+    let dataset: Value = read_json(&iteration_path(iteration).join(DATASET_FILE)).await?;
+
+    mock_api
+        .request_json(Method::PUT, "database", &dataset)
+        .await
+        .context("Failed to load the dataset into the mock server")?;
+
+    Ok(())
+}
+
+/// Reads the recorded responses to every step of the scenario
+pub(crate) async fn load_snapshot(iteration: usize, scenario: &Scenario) -> Result<Vec<Value>> {
+    let snapshots: Vec<Snapshot> =
+        read_json(&iteration_path(iteration).join(format!("{}.json", scenario.name))).await?;
+
+    let requests = snapshots.iter().map(|snapshot| &snapshot.request);
+
+    if !requests.eq(&scenario.steps) {
+        bail!(
+            "Snapshot of `{}` is outdated, run `cargo run -- record` to update it",
+            scenario.name
+        );
+    }
+
+    Ok(snapshots
+        .into_iter()
+        .map(|snapshot| snapshot.response)
+        .collect())
+}
+
+async fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value)?).await?;
+
+    Ok(())
+}
+
+async fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = fs::read_to_string(path).await.with_context(|| {
+        format!(
+            "Failed to read snapshot `{}`, run `cargo run -- record` first",
+            path.display()
+        )
+    })?;
+
+    Ok(serde_json::from_str(&content)?)
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+/// Two-sided 95% critical values of Student's t-distribution
+/// for 1..=30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Spread of a value measured in repeated benchmark runs
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Spread {
+    pub samples: Vec<u64>,
+    pub median: u64,
+    pub mean: f64,
+    pub stddev: f64,
+    /// Bounds of the 95% confidence interval of the mean
+    pub ci95: (f64, f64),
+    /// Coefficient of variation, i.e. stddev relative to the mean
+    pub cv: f64,
+}
+
+impl Spread {
+    pub fn new(samples: Vec<u64>) -> Self {
+        let n = samples.len();
+
+        if n == 0 {
+            return Self::default();
+        }
+
+        let mut sorted = samples.clone();
+        sorted.sort_unstable();
+
+        let median = if n % 2 == 0 {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+        } else {
+            sorted[n / 2]
+        };
+
+        let mean = samples.iter().sum::<u64>() as f64 / n as f64;
+
+        let stddev = if n > 1 {
+            let variance = samples
+                .iter()
+                .map(|&x| (x as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1) as f64;
+
+            variance.sqrt()
+        } else {
+            0.0
+        };
+
+        let margin = if n > 1 {
+            let t = T_95.get(n - 2).copied().unwrap_or(1.96);
+
+            t * stddev / (n as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        let cv = if mean > 0.0 { stddev / mean } else { 0.0 };
+
+        Self {
+            samples,
+            median,
+            mean,
+            stddev,
+            ci95: (mean - margin, mean + margin),
+            cv,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spread;
+
+    #[test]
+    fn test_spread() {
+        let spread = Spread::new(vec![100, 110, 90, 105, 95]);
+
+        assert_eq!(spread.median, 100);
+        assert_eq!(spread.mean, 100.0);
+        assert!((spread.stddev - 7.9057).abs() < 1e-4);
+        assert!((spread.ci95.0 - 90.1854).abs() < 1e-3);
+        assert!((spread.ci95.1 - 109.8146).abs() < 1e-3);
+        assert!((spread.cv - 0.079057).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_spread_single_sample() {
+        let spread = Spread::new(vec![42]);
+
+        assert_eq!(spread.median, 42);
+        assert_eq!(spread.stddev, 0.0);
+        assert_eq!(spread.ci95, (42.0, 42.0));
+        assert_eq!(spread.cv, 0.0);
+    }
+
+    #[test]
+    fn test_spread_even_samples() {
+        assert_eq!(Spread::new(vec![10, 40, 20, 30]).median, 25);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{fmt::Write, path::Path, sync::LazyLock};
+
+use anyhow::Result;
+use regex::Regex;
+use serde::Serialize;
+use tokio::fs;
+
+static ANSI_ESCAPE: LazyLock<Regex> = LazyLock::new(|| Regex::new("\x1b\\[[0-9;]*m").unwrap());
+
+/// Result of a single test in a single iteration
+#[derive(Serialize, Debug)]
+pub(crate) struct TestCase {
+    pub(crate) name: String,
+    pub(crate) iteration: usize,
+    /// Seed of the mock data, not set when the data is restored from the snapshots
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) seed: Option<u64>,
+    /// Duration in seconds
+    pub(crate) time: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) failure: Option<TestFailure>,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct TestFailure {
+    pub(crate) message: String,
+    /// Difference between expected and actual responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) diff: Option<String>,
+}
+
+impl TestFailure {
+    pub(crate) fn new(message: impl Into<String>, diff: Option<&str>) -> Self {
+        Self {
+            message: message.into(),
+            diff: diff.map(|diff| ANSI_ESCAPE.replace_all(diff, "").into_owned()),
+        }
+    }
+}
+
+/// All test cases of a single run of the graphql tests
+#[derive(Serialize, Debug)]
+pub(crate) struct TestSuite {
+    pub(crate) name: String,
+    pub(crate) tests: usize,
+    pub(crate) failures: usize,
+    pub(crate) cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            tests: 0,
+            failures: 0,
+            cases: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, case: TestCase) {
+        self.tests += 1;
+
+        if case.failure.is_some() {
+            self.failures += 1;
+        }
+
+        self.cases.push(case);
+    }
+}
+
+/// Test suites of the project run
+#[derive(Serialize, Debug, Default)]
+pub(crate) struct TestReport {
+    pub(crate) suites: Vec<TestSuite>,
+}
+
+impl TestReport {
+    pub(crate) fn to_junit(&self) -> String {
+        let tests: usize = self.suites.iter().map(|suite| suite.tests).sum();
+        let failures: usize = self.suites.iter().map(|suite| suite.failures).sum();
+
+        let mut content = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_owned();
+        let _ = writeln!(
+            content,
+            r#"<testsuites name="graphql-tests" tests="{tests}" failures="{failures}">"#
+        );
+
+        for suite in &self.suites {
+            let time: f64 = suite.cases.iter().map(|case| case.time).sum();
+
+            let _ = writeln!(
+                content,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" time="{time:.3}">"#,
+                escape_xml(&suite.name),
+                suite.tests,
+                suite.failures,
+            );
+
+            for case in &suite.cases {
+                let _ = write!(
+                    content,
+                    r#"    <testcase name="{} #{}" classname="{}" time="{:.3}""#,
+                    escape_xml(&case.name),
+                    case.iteration,
+                    escape_xml(&suite.name),
+                    case.time,
+                );
+
+                let Some(failure) = &case.failure else {
+                    content.push_str("/>\n");
+                    continue;
+                };
+
+                let mut details = format!("Iteration: {}", case.iteration);
+
+                if let Some(seed) = case.seed {
+                    let _ = write!(details, ", mock data seed: {seed}");
+                }
+
+                if let Some(diff) = &failure.diff {
+                    let _ = write!(details, "\n{diff}");
+                }
+
+                let _ = writeln!(
+                    content,
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                    escape_xml(&failure.message),
+                    escape_xml(&details),
+                );
+            }
+
+            content.push_str("  </testsuite>\n");
+        }
+
+        content.push_str("</testsuites>\n");
+
+        content
+    }
+
+    /// Writes `junit.xml` and `test-report.json` into the directory
+    pub(crate) async fn write(&self, output_path: &Path) -> Result<()> {
+        fs::create_dir_all(output_path).await?;
+        fs::write(output_path.join("junit.xml"), self.to_junit()).await?;
+        fs::write(
+            output_path.join("test-report.json"),
+            serde_json::to_string_pretty(self)?,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TestCase, TestFailure, TestReport, TestSuite};
+
+    #[test]
+    fn test_junit() {
+        let mut suite = TestSuite::new("graphql-tests");
+
+        suite.push(TestCase {
+            name: "posts-title".to_owned(),
+            iteration: 1,
+            seed: Some(42),
+            time: 0.0125,
+            failure: None,
+        });
+        suite.push(TestCase {
+            name: "posts-nested".to_owned(),
+            iteration: 1,
+            seed: Some(42),
+            time: 0.5,
+            failure: Some(TestFailure::new(
+                "Actual response is not equal to expected",
+                Some("\u{1b}[31m~ title: \"<a>\" -> \"b\"\u{1b}[0m"),
+            )),
+        });
+
+        assert_eq!(suite.tests, 2);
+        assert_eq!(suite.failures, 1);
+
+        let report = TestReport {
+            suites: vec![suite],
+        };
+
+        assert_eq!(
+            report.to_junit(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="graphql-tests" tests="2" failures="1">
+  <testsuite name="graphql-tests" tests="2" failures="1" time="0.512">
+    <testcase name="posts-title #1" classname="graphql-tests" time="0.013"/>
+    <testcase name="posts-nested #1" classname="graphql-tests" time="0.500">
+      <failure message="Actual response is not equal to expected">Iteration: 1, mock data seed: 42
+~ title: &quot;&lt;a&gt;&quot; -&gt; &quot;b&quot;</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
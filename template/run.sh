@@ -5,3 +5,5 @@ set -e
 # TODO: implement installation
 
 # TODO: run the server
+# The server should listen on port ${SERVER_PORT:-8000}
+# and fetch data from http://localhost:${MOCK_SERVER_PORT:-3000}